@@ -0,0 +1,186 @@
+use std::hash::Hash;
+
+use crate::{collapsing_header::CollapsingState, *};
+
+/// A group of [`CollapsingHeader`]s of which only one is open at a time.
+///
+/// Opening one of the headers closes the others, which animate closed as usual.
+/// If several headers are open without one being opened this frame
+/// (e.g. with [`CollapsingHeader::default_open`]), only the first of them is kept open.
+///
+/// ```
+/// # egui::__run_test_ui(|ui| {
+/// egui::Accordion::new("settings").show(ui, |accordion| {
+///     accordion.header("General", |ui| ui.label("…"));
+///     accordion.header("Advanced", |ui| ui.label("…"));
+/// });
+/// # });
+/// ```
+#[must_use = "You should call .show()"]
+pub struct Accordion {
+    id_source: Id,
+}
+
+impl Accordion {
+    pub fn new(id_source: impl Hash) -> Self {
+        Self {
+            id_source: Id::new(id_source),
+        }
+    }
+
+    /// Show the headers added in `add_contents`.
+    pub fn show<R>(
+        self,
+        ui: &mut Ui,
+        add_contents: impl FnOnce(&mut AccordionUi<'_>) -> R,
+    ) -> InnerResponse<R> {
+        ui.push_id(self.id_source, |ui| {
+            let mut accordion = AccordionUi {
+                ui,
+                headers: vec![],
+                opened: None,
+            };
+            let ret = add_contents(&mut accordion);
+
+            let AccordionUi {
+                ui,
+                headers,
+                opened,
+            } = accordion;
+            // If no header was opened this frame (e.g. several start open, from `default_open`
+            // or stored state), keep the first open one:
+            let is_open =
+                |id: Id| CollapsingState::load(ui.ctx(), id).map_or(false, |state| state.is_open());
+            let keep = opened.or_else(|| headers.iter().copied().find(|&id| is_open(id)));
+            if let Some(keep) = keep {
+                let mut closed_any = false;
+                for id in headers.into_iter().filter(|&id| id != keep) {
+                    if let Some(mut state) = CollapsingState::load(ui.ctx(), id) {
+                        if state.is_open() {
+                            state.set_open(false);
+                            state.store(ui.ctx());
+                            closed_any = true;
+                        }
+                    }
+                }
+                if closed_any {
+                    ui.ctx().request_repaint();
+                }
+            }
+            ret
+        })
+    }
+}
+
+/// Adds headers to an [`Accordion`].
+pub struct AccordionUi<'ui> {
+    ui: &'ui mut Ui,
+
+    /// The ids of the headers shown so far.
+    headers: Vec<Id>,
+
+    /// The header that was opened this frame.
+    opened: Option<Id>,
+}
+
+impl<'ui> AccordionUi<'ui> {
+    /// Add a [`CollapsingHeader`] with this title.
+    pub fn header<R>(
+        &mut self,
+        text: impl Into<WidgetText>,
+        add_body: impl FnOnce(&mut Ui) -> R,
+    ) -> CollapsingResponse<R> {
+        self.collapsing_header(CollapsingHeader::new(text), add_body)
+    }
+
+    /// Add a customized [`CollapsingHeader`].
+    ///
+    /// Don't set [`CollapsingHeader::open`], or the [`Accordion`] can't close it.
+    pub fn collapsing_header<R>(
+        &mut self,
+        header: CollapsingHeader,
+        add_body: impl FnOnce(&mut Ui) -> R,
+    ) -> CollapsingResponse<R> {
+        let response = header.show(self.ui, add_body);
+        let id = response.header_response.id;
+        self.headers.push(id);
+        if response.header_response.changed()
+            && CollapsingState::load(self.ui.ctx(), id).map_or(false, |state| state.is_open())
+        {
+            self.opened = Some(id);
+        }
+        response
+    }
+
+    /// The [`Ui`] the headers are shown in, e.g. to add something between them.
+    pub fn ui(&mut self) -> &mut Ui {
+        self.ui
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{click, run_frame};
+
+    #[test]
+    fn opening_a_header_closes_the_others() {
+        let ctx = Context::default();
+        let show = |events: Vec<Event>| {
+            let mut headers = vec![];
+            run_frame(&ctx, events, |ui| {
+                Accordion::new("accordion").show(ui, |accordion| {
+                    for title in ["A", "B"] {
+                        let response = accordion.header(title, |ui| ui.label("Body"));
+                        headers.push((response.header_response.rect, response.openness));
+                    }
+                });
+            });
+            headers
+        };
+        let click = |rect: Rect| click(rect.center(), show);
+
+        let headers = show(vec![]);
+        click(headers[0].0);
+        for _ in 0..30 {
+            show(vec![]);
+        }
+        let headers = show(vec![]);
+        assert_eq!((headers[0].1, headers[1].1), (1.0, 0.0));
+
+        click(headers[1].0);
+        let closing = show(vec![]);
+        assert!(
+            0.0 < closing[0].1 && closing[0].1 < 1.0,
+            "A animates closed, at {}",
+            closing[0].1
+        );
+        for _ in 0..30 {
+            show(vec![]);
+        }
+        let headers = show(vec![]);
+        assert_eq!((headers[0].1, headers[1].1), (0.0, 1.0));
+    }
+
+    #[test]
+    fn only_the_first_default_open_header_stays_open() {
+        let ctx = Context::default();
+        let show = || {
+            let mut openness = vec![];
+            run_frame(&ctx, vec![], |ui| {
+                Accordion::new("accordion").show(ui, |accordion| {
+                    for title in ["A", "B", "C"] {
+                        let header = CollapsingHeader::new(title).default_open(title != "A");
+                        let response = accordion.collapsing_header(header, |ui| ui.label("Body"));
+                        openness.push(response.openness);
+                    }
+                });
+            });
+            openness
+        };
+        for _ in 0..30 {
+            show();
+        }
+        assert_eq!(show(), vec![0.0, 1.0, 0.0]);
+    }
+}
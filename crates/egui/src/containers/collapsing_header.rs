@@ -1,9 +1,20 @@
-use std::hash::Hash;
+mod flyout;
+mod group;
+mod radio;
+mod schema;
+mod visible_rows;
 
-use crate::*;
-use epaint::Shape;
+use std::{cell::Cell, collections::VecDeque, hash::Hash, ops::RangeInclusive, sync::Arc};
 
-#[derive(Clone, Copy, Debug)]
+use crate::{mutex::Mutex, util::id_type_map::SerializableAny, *};
+use epaint::{ClippedShape, Shape};
+
+pub use group::CollapsingGroup;
+pub use radio::CollapsingRadio;
+pub use schema::{CollapsingSchema, SchemaLeaf};
+use {flyout::show_flyout, visible_rows::show_visible_rows};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 pub(crate) struct InnerState {
     open: bool,
@@ -11,6 +22,43 @@ pub(crate) struct InnerState {
     /// Height of the region when open. Used for animations
     #[cfg_attr(feature = "serde", serde(default))]
     open_height: Option<f32>,
+
+    /// Showing everything, rather than just peeking. See [`CollapsingHeader::peek_height`].
+    #[cfg_attr(feature = "serde", serde(default))]
+    fully_open: bool,
+
+    /// Height of the body the last time it was shown or measured,
+    /// see [`CollapsingHeader::auto_expand_on_growth`].
+    #[cfg_attr(feature = "serde", serde(default))]
+    last_content_height: Option<f32>,
+
+    /// Height of all of the body, if it was capped by [`CollapsingHeader::max_body_height`].
+    #[cfg_attr(feature = "serde", serde(default))]
+    full_content_height: Option<f32>,
+
+    /// How many times the region has been opened or closed. See [`CollapsingState::change_token`].
+    #[cfg_attr(feature = "serde", serde(default))]
+    toggle_count: u32,
+
+    /// Pinned regions stay open when collapsing everything. See [`CollapsingState::set_pinned`].
+    #[cfg_attr(feature = "serde", serde(default))]
+    pinned: bool,
+
+    /// Width of the region when open, if it opens sideways. See [`CollapsingState::set_horizontal`].
+    #[cfg_attr(feature = "serde", serde(default))]
+    open_width: Option<f32>,
+
+    /// Hidden altogether, not just collapsed. See [`CollapsingState::set_hidden`].
+    #[cfg_attr(feature = "serde", serde(default))]
+    hidden: bool,
+
+    /// Stored as temporary data, so it isn't persisted. See [`CollapsingState::set_persist`].
+    #[cfg_attr(feature = "serde", serde(default))]
+    temp: bool,
+
+    /// The last value passed to [`CollapsingHeader::set_open_once`].
+    #[cfg_attr(feature = "serde", serde(default))]
+    open_once: Option<bool>,
 }
 
 /// This is a a building block for building collapsing regions.
@@ -21,37 +69,317 @@ pub(crate) struct InnerState {
 #[derive(Clone, Debug)]
 pub struct CollapsingState {
     id: Id,
+
+    /// Id used for the open/close animation. Usually the same as `id`.
+    anim_id: Id,
+
+    /// If set, used instead of the animated openness.
+    openness_override: Option<f32>,
+
+    /// Pretend the body is only as tall as the visible part while animating.
+    clamp_body_rect: bool,
+
+    /// Open, but waiting for the body to be ready, so shown as closed.
+    pending: bool,
+
+    /// How the body appears while animating.
+    reveal_style: RevealStyle,
+
+    /// Paint diagnostics of the body animation.
+    debug: bool,
+
+    /// If false, [`Self::store`] does nothing.
+    manage_state: bool,
+
+    /// Bodies shorter than this open and close without animating.
+    min_animate_height: f32,
+
+    /// Fade out this much of the bottom of the body while animating.
+    fade_edge: f32,
+
+    /// How to ease the openness.
+    animation: AnimationStyle,
+
+    /// How long the animation takes, instead of [`Style::animation_time`].
+    animation_time: Option<f32>,
+
+    /// The body is to the right of the header, and is revealed from left to right.
+    horizontal: bool,
+
+    /// Where [`Self::show_header`] puts the toggle button.
+    icon_side: HeaderIconSide,
+
     state: InnerState,
 }
 
 impl CollapsingState {
+    fn from_state(id: Id, state: InnerState) -> Self {
+        Self {
+            id,
+            anim_id: id,
+            openness_override: None,
+            clamp_body_rect: true,
+            pending: false,
+            reveal_style: RevealStyle::Clip,
+            debug: false,
+            manage_state: true,
+            min_animate_height: 0.0,
+            fade_edge: 0.0,
+            animation: AnimationStyle::Responsive,
+            animation_time: None,
+            horizontal: false,
+            icon_side: HeaderIconSide::Left,
+            state,
+        }
+    }
+
     pub fn load(ctx: &Context, id: Id) -> Option<Self> {
         ctx.data_mut(|d| {
             d.get_persisted::<InnerState>(id)
-                .map(|state| Self { id, state })
+                .map(|state| Self::from_state(id, state))
         })
     }
 
     pub fn store(&self, ctx: &Context) {
-        ctx.data_mut(|d| d.insert_persisted(self.id, self.state));
+        if !self.manage_state {
+            return;
+        }
+        let opened_at_id = self.id.with("opened_at");
+
+        // Most regions are stored every frame without changing, so check that without a write lock.
+        // A state that is still serialized isn't found here, and is written below.
+        let unchanged = ctx.data(|d| {
+            let has_opened_at = d.get_temp::<f64>(opened_at_id).is_some();
+            d.get_temp::<InnerState>(self.id) == Some(self.state)
+                && has_opened_at == self.state.open
+        });
+        if unchanged {
+            return;
+        }
+
+        let time = ctx.input(|i| i.time);
+        ctx.data_mut(|d| {
+            let stored = d.get_persisted::<InnerState>(self.id);
+            if self.state.open {
+                let was_open = stored.map(|s| s.open);
+                if was_open != Some(true) || d.get_temp::<f64>(opened_at_id).is_none() {
+                    d.insert_temp(opened_at_id, time);
+                }
+            } else {
+                d.remove::<f64>(opened_at_id);
+            }
+            if self.state.temp {
+                d.insert_temp(self.id, self.state);
+            } else {
+                d.insert_persisted(self.id, self.state);
+            }
+        });
+    }
+
+    /// Remove the stored state of the regions shown inside the body of the header with the id `parent`
+    /// for which `keep` returns `false`, e.g. the rows of a dynamic list that are gone.
+    ///
+    /// Everything stored for a removed region is removed, including its [`Self::set_user_data`],
+    /// and the regions inside a removed region are removed too.
+    /// Use [`Id::NULL`] as `parent` for the regions that are not inside any other header.
+    ///
+    /// This is scoped to a parent, rather than asking about every region there is,
+    /// so that `keep` only needs to know about your own rows, and not about e.g. every [`Window`].
+    ///
+    /// Only the regions shown inside `parent` during the last two frames are known
+    /// (which [`CollapsingHeader`]s register by themselves, see [`Self::register`]),
+    /// or the last time its body was shown, if it is closed.
+    /// So call this every frame, or at least in the frame your rows change.
+    /// Without this, the state of every region that was ever shown stays in memory,
+    /// and is persisted with the `persistence` feature.
+    ///
+    /// ```
+    /// # egui::__run_test_ui(|ui| {
+    /// # let rows: Vec<egui::Id> = vec![];
+    /// use egui::collapsing_header::CollapsingState;
+    ///
+    /// let list = egui::CollapsingHeader::new("Rows").show(ui, |ui| {
+    ///     // Show the rows…
+    /// });
+    /// CollapsingState::retain(ui.ctx(), list.header_response.id, |id| rows.contains(&id));
+    /// # });
+    /// ```
+    pub fn retain(ctx: &Context, parent: Id, mut keep: impl FnMut(Id) -> bool) {
+        let children = HeaderRegistry::read(ctx, |registry| registry.recent_children(parent));
+        // Outside of the registry, so that `keep` can use the context:
+        let removed: Vec<Id> = children.into_iter().filter(|&id| !keep(id)).collect();
+        if removed.is_empty() {
+            return;
+        }
+        let removed = HeaderRegistry::write(ctx, |registry| {
+            let mut removed_all = vec![];
+            let mut stack = removed;
+            while let Some(id) = stack.pop() {
+                stack.extend(registry.recent_children(id));
+                registry.forget(id);
+                removed_all.push(id);
+            }
+            removed_all
+        });
+        ctx.data_mut(|d| {
+            for id in removed {
+                Self::remove_stored(d, id);
+            }
+        });
+    }
+
+    /// Remove everything stored for the region with the given id.
+    fn remove_stored(d: &mut crate::util::IdTypeMap, id: Id) {
+        d.remove::<InnerState>(id);
+        if let Some(removers) = d.get_temp::<UserDataRemovers>(id.with("user_data_removers")) {
+            for (_, remove_user_data) in removers.0 {
+                remove_user_data(d, id);
+            }
+        }
+        d.remove::<UserDataRemovers>(id.with("user_data_removers"));
+        d.remove::<f64>(id.with("opened_at"));
+        d.remove::<f32>(id.with("prev_openness"));
+        d.remove::<EaseSegment>(id.with("ease_segment"));
+        d.remove::<BodyCache>(id.with("body_cache"));
+        d.remove::<(u64, Arc<Galley>)>(id.with("collapsed_galley"));
+        d.remove::<f64>(id.with("drop_hovered_since"));
+        d.remove::<String>(id.with("title_edit"));
+        d.remove::<Rect>(id.with("body_background"));
+        d.remove::<Rect>(id.with("full_click_row"));
+        d.remove::<Rect>(id.with("selectable_row"));
+        d.remove::<bool>(id.with("uncapped"));
+        d.remove::<f32>(id.with("peek_content_height"));
+    }
+
+    /// Like [`Self::store`], but without persisting the state, see [`Self::set_persist`].
+    pub fn store_temp(&self, ctx: &Context) {
+        let mut state = self.clone();
+        state.set_persist(false);
+        state.store(ctx);
+    }
+
+    /// Like [`Self::load_with_default_open`], but for a state that isn't persisted, see [`Self::set_persist`].
+    pub fn load_temp(ctx: &Context, id: Id, default_open: bool) -> Self {
+        let mut state = Self::load_with_default_open(ctx, id, default_open);
+        state.set_persist(false);
+        state
+    }
+
+    /// Should [`Self::store`] persist the state, e.g. with the `persistence` feature? Default: `true`.
+    ///
+    /// Turn this off for many short-lived regions, e.g. the nodes of a big tree,
+    /// so that their open state doesn't bloat the saved app state.
+    /// The choice is stored with the state, so it sticks when the state is loaded again,
+    /// e.g. by the body or by [`Self::set_subtree_open`].
+    pub fn set_persist(&mut self, persist: bool) {
+        self.state.temp = !persist;
     }
 
+    /// Remove everything stored for this region, like [`Self::retain`] does.
     pub fn remove(&self, ctx: &Context) {
-        ctx.data_mut(|d| d.remove::<InnerState>(self.id));
+        ctx.data_mut(|d| Self::remove_stored(d, self.id));
+        HeaderRegistry::write(ctx, |registry| registry.forget(self.id));
     }
 
     pub fn id(&self) -> Id {
         self.id
     }
 
+    /// The [`Id`] used for the open/close animation.
+    ///
+    /// Same as [`Self::id`] unless changed with [`Self::set_anim_id`].
+    pub fn anim_id(&self) -> Id {
+        self.anim_id
+    }
+
+    /// Animate the openness using a different [`Id`] than the one used for storing the state.
+    ///
+    /// This lets several visual instances share the same stored open state
+    /// while each animating on their own.
+    pub fn set_anim_id(&mut self, anim_id: Id) {
+        self.anim_id = anim_id;
+    }
+
     pub fn load_with_default_open(ctx: &Context, id: Id, default_open: bool) -> Self {
-        Self::load(ctx, id).unwrap_or(Self {
+        Self::load(ctx, id).unwrap_or_else(|| Self::new(id, default_open))
+    }
+
+    /// A state that hasn't been stored, nor shown, yet.
+    fn new(id: Id, open: bool) -> Self {
+        Self::from_state(
             id,
-            state: InnerState {
-                open: default_open,
+            InnerState {
+                open,
                 open_height: None,
+                fully_open: false,
+                full_content_height: None,
+                last_content_height: None,
+                toggle_count: 0,
+                pinned: false,
+                open_width: None,
+                hidden: false,
+                temp: false,
+                open_once: None,
             },
-        })
+        )
+    }
+
+    /// Open and close without animating if the body is shorter than this. Default: 0 (always animate).
+    ///
+    /// This avoids animations of just a few pixels, which can look like glitches.
+    /// Uses the height of the body from the last time it was shown, so the first opening is animated.
+    pub fn set_min_animate_height(&mut self, min_animate_height: f32) {
+        self.min_animate_height = min_animate_height;
+    }
+
+    /// While animating, fade out the bottom `fade_edge` points of the visible part of the body,
+    /// instead of cutting it off sharply. Default: 0 (sharp).
+    ///
+    /// Only with [`RevealStyle::Clip`].
+    pub fn set_fade_edge(&mut self, fade_edge: f32) {
+        self.fade_edge = fade_edge;
+    }
+
+    /// How to ease between closed and open. Default: [`AnimationStyle::Responsive`].
+    ///
+    /// With [`AnimationStyle::Overshoot`], [`Self::openness`] is briefly above 1 while opening.
+    pub fn set_animation(&mut self, animation: AnimationStyle) {
+        self.animation = animation;
+    }
+
+    /// How many seconds opening and closing takes.
+    ///
+    /// `None` (the default) uses [`Style::animation_time`].
+    pub fn set_animation_time(&mut self, animation_time: Option<f32>) {
+        self.animation_time = animation_time;
+    }
+
+    fn animation_time(&self, ctx: &Context) -> f32 {
+        self.animation_time
+            .unwrap_or_else(|| ctx.style().animation_time)
+    }
+
+    /// Reveal the body from left to right instead of from the top down,
+    /// for a body to the right of the header, e.g. in a toolbar.
+    ///
+    /// [`CollapsingHeader`] does this by itself in a horizontal layout.
+    pub fn set_horizontal(&mut self, horizontal: bool) {
+        self.horizontal = horizontal;
+    }
+
+    /// Where [`Self::show_header`] puts the toggle button. Default: [`HeaderIconSide::Left`].
+    ///
+    /// With [`HeaderIconSide::Right`] the button goes after the header, at the right edge of the row.
+    pub fn set_icon_side(&mut self, icon_side: HeaderIconSide) {
+        self.icon_side = icon_side;
+    }
+
+    /// Should [`Self::store`] write the state to the [`Context`]? Default: `true`.
+    ///
+    /// Turn this off if the state comes from your own model, and is recreated every frame.
+    /// See [`CollapsingHeader::manage_state`].
+    pub fn set_manage_state(&mut self, manage_state: bool) {
+        self.manage_state = manage_state;
     }
 
     pub fn is_open(&self) -> bool {
@@ -59,20 +387,449 @@ impl CollapsingState {
     }
 
     pub fn set_open(&mut self, open: bool) {
+        if self.state.open != open {
+            self.state.toggle_count = self.state.toggle_count.wrapping_add(1);
+        }
         self.state.open = open;
+        if !open {
+            self.state.fully_open = false;
+        }
     }
 
     pub fn toggle(&mut self, ui: &Ui) {
-        self.state.open = !self.state.open;
+        self.set_open(!self.state.open);
+        ui.ctx().request_repaint();
+    }
+
+    pub fn is_hidden(&self) -> bool {
+        self.state.hidden
+    }
+
+    /// Hide the whole [`Window`] using this state, not just its body.
+    ///
+    /// Unlike closing, the window fades out, see [`Self::visibility`].
+    pub fn set_hidden(&mut self, hidden: bool) {
+        self.state.hidden = hidden;
+    }
+
+    pub fn toggle_hidden(&mut self, ui: &Ui) {
+        self.set_hidden(!self.state.hidden);
         ui.ctx().request_repaint();
     }
 
-    /// 0 for closed, 1 for open, with tweening
+    /// 0 if hidden, 1 if not, and something in between while animating.
+    ///
+    /// Multiply the opacity with this, and stop showing anything once it reaches 0.
+    /// Animates independently of [`Self::openness`].
+    pub fn visibility(&self, ctx: &Context) -> f32 {
+        ctx.animate_bool(self.id.with("hidden"), !self.is_hidden())
+    }
+
+    /// A value that changes every time the region is opened or closed, and stays the same otherwise.
+    ///
+    /// Compare it to the last one you saw to find out if the open state changed,
+    /// e.g. to take a snapshot for an undo stack, without comparing whole states.
+    pub fn change_token(&self) -> u32 {
+        self.state.toggle_count
+    }
+
+    /// Open or close the region without animating, e.g. when restoring a saved layout.
+    ///
+    /// Like [`Self::set_open`] followed by [`Self::reset_animation`].
+    /// Remember to [`Self::store`] the state.
+    pub fn set_open_instant(&mut self, open: bool, ctx: &Context) {
+        self.set_open(open);
+        ctx.animate_bool_with_time(self.anim_id, open, 0.0);
+        ctx.request_repaint();
+    }
+
+    /// The height of all of the body the last time it was shown,
+    /// including any parts hidden by [`CollapsingHeader::max_body_height`].
+    ///
+    /// `None` if the body hasn't been shown yet.
+    pub fn full_content_height(&self) -> Option<f32> {
+        self.state.full_content_height.or(self.state.open_height)
+    }
+
+    /// Is the whole body shown, rather than just a peek of it?
+    ///
+    /// Only has an effect with [`CollapsingHeader::peek_height`].
+    /// Closing the region resets this, so it peeks again the next time it is opened.
+    pub fn is_fully_open(&self) -> bool {
+        self.state.open && self.state.fully_open
+    }
+
+    /// Show the whole body, rather than just a peek of it.
+    ///
+    /// See [`Self::is_fully_open`].
+    pub fn set_fully_open(&mut self, fully_open: bool) {
+        self.state.fully_open = fully_open;
+    }
+
+    /// The time (see [`InputState::time`]) when this region was last stored as open,
+    /// or `None` if it is closed.
+    ///
+    /// Regions that were already open when the app started count as opened
+    /// the first time they are stored.
+    pub fn opened_at(&self, ctx: &Context) -> Option<f64> {
+        if self.state.open {
+            ctx.data(|d| d.get_temp(self.id.with("opened_at")))
+        } else {
+            None
+        }
+    }
+
+    /// For how many seconds this region has been open, or `None` if it is closed.
+    ///
+    /// See [`Self::opened_at`].
+    pub fn open_duration(&self, ctx: &Context) -> Option<f64> {
+        let opened_at = self.opened_at(ctx)?;
+        Some(ctx.input(|i| i.time) - opened_at)
+    }
+
+    /// The value last stored with [`Self::set_user_data`] or [`CollapsingHeader::with_user_data`]
+    /// for the header with the given id.
+    ///
+    /// It is persisted together with the open state, so it can be any type that can be persisted.
+    pub fn user_data<T: SerializableAny>(ctx: &Context, id: Id) -> Option<T> {
+        ctx.data_mut(|d| d.get_persisted(id))
+    }
+
+    /// Store app-specific data (e.g. a user note) for the header with the given id,
+    /// persisted together with its open state.
+    ///
+    /// Read it back with [`Self::user_data`].
+    pub fn set_user_data<T: SerializableAny>(ctx: &Context, id: Id, user_data: T) {
+        ctx.data_mut(|d| {
+            d.insert_persisted(id, user_data);
+            // Only `T` knows where the data is, so remember how to remove it, for `retain`:
+            let removers =
+                d.get_temp_mut_or_default::<UserDataRemovers>(id.with("user_data_removers"));
+            let type_id = std::any::TypeId::of::<T>();
+            if removers.0.iter().all(|(other, _)| *other != type_id) {
+                removers.0.push((type_id, |d, id| d.remove::<T>(id)));
+            }
+        });
+    }
+
+    /// The ids of the [`CollapsingHeader`]s that were shown directly inside the body of
+    /// the header with the given id during the previous frame, in the order they were shown.
+    ///
+    /// Use [`Id::NULL`] as `parent` to get the headers that were not inside any other header.
+    ///
+    /// The children of a closed header are remembered from the last time its body was shown.
+    ///
+    /// The order only depends on the order the headers are shown in,
+    /// so it is stable across frames and suitable for e.g. serialization.
+    pub fn children_of(ctx: &Context, parent: Id) -> Vec<Id> {
+        HeaderRegistry::read(ctx, |registry| {
+            registry
+                .prev_children
+                .get(&parent)
+                .cloned()
+                .unwrap_or_default()
+        })
+    }
+
+    /// Open or close all [`CollapsingHeader`]s inside the header with the given id,
+    /// recursively, as known from [`Self::children_of`].
+    ///
+    /// Does not change the state of the `root` header itself.
+    /// [Pinned](Self::set_pinned) headers are left open when closing.
+    pub fn set_subtree_open(ctx: &Context, root: Id, open: bool) {
+        for id in HeaderRegistry::read(ctx, |registry| registry.descendants(root)) {
+            if let Some(mut state) = Self::load(ctx, id) {
+                if open || !state.is_pinned() {
+                    state.set_open(open);
+                    state.store(ctx);
+                }
+            }
+        }
+        ctx.request_repaint();
+    }
+
+    /// How many of the [`CollapsingHeader`]s inside the header with the given id are open,
+    /// recursively, as known from [`Self::children_of`].
+    ///
+    /// Use [`Id::NULL`] as `root` to count all headers.
+    /// This counts the stored open state, so a header that is still animating closed is not counted.
+    pub fn count_open(ctx: &Context, root: Id) -> usize {
+        HeaderRegistry::read(ctx, |registry| registry.descendants(root))
+            .into_iter()
+            .filter(|&id| Self::load(ctx, id).map_or(false, |state| state.is_open()))
+            .count()
+    }
+
+    /// The open states of the given headers, packed into bits, e.g. for saving the state of a big tree.
+    ///
+    /// Bit `i % 64` of word `i / 64` is set if `ids[i]` is open.
+    /// Headers that haven't been shown yet count as closed.
+    /// Restore it with [`Self::apply_open_bitmask`], using the same ids in the same order,
+    /// e.g. from [`Self::children_of`].
+    pub fn open_bitmask(ctx: &Context, ids: &[Id]) -> Vec<u64> {
+        let mut bits = vec![0; ids.len().div_ceil(64)];
+        for (i, &id) in ids.iter().enumerate() {
+            if Self::load(ctx, id).map_or(false, |state| state.is_open()) {
+                bits[i / 64] |= 1 << (i % 64);
+            }
+        }
+        bits
+    }
+
+    /// Open or close the given headers, as stored by [`Self::open_bitmask`].
+    ///
+    /// `ids` must be in the same order as when the bitmask was made.
+    /// Headers beyond the end of `bits` are closed.
+    pub fn apply_open_bitmask(ctx: &Context, ids: &[Id], bits: &[u64]) {
+        for (i, &id) in ids.iter().enumerate() {
+            let open = bits
+                .get(i / 64)
+                .map_or(false, |word| word & (1 << (i % 64)) != 0);
+            let mut state = Self::load_with_default_open(ctx, id, open);
+            state.set_open(open);
+            state.store(ctx);
+        }
+        ctx.request_repaint();
+    }
+
+    /// Close all [`CollapsingHeader`]s shown in the previous frame, except the [pinned](Self::set_pinned) ones.
+    pub fn collapse_all(ctx: &Context) {
+        Self::set_all_open(ctx, false);
+    }
+
+    /// Open or close all [`CollapsingHeader`]s shown in the previous frame,
+    /// e.g. for "Expand all" and "Collapse all" buttons.
+    ///
+    /// [Pinned](Self::set_pinned) headers are left open when closing.
+    /// Headers that are no longer shown are forgotten, so they are not affected.
+    /// Regions shown without a [`CollapsingHeader`] are included once they [`Self::register`].
+    pub fn set_all_open(ctx: &Context, open: bool) {
+        // The top-level headers are the children of `Id::NULL`:
+        Self::set_subtree_open(ctx, Id::NULL, open);
+    }
+
+    /// Let [`Self::set_all_open`] and friends know about this region, for this frame.
+    ///
+    /// [`CollapsingHeader`]s do this by themselves; call it each frame for a region with a custom header,
+    /// e.g. from [`Self::show_header`].
+    /// The region counts as a child of the [`CollapsingHeader`] whose body it is shown in, if any.
+    pub fn register(&self, ctx: &Context) {
+        HeaderRegistry::register_child(ctx, self.id);
+    }
+
+    /// Is the region pinned open? See [`Self::set_pinned`].
+    pub fn is_pinned(&self) -> bool {
+        self.state.pinned
+    }
+
+    /// Pin the region, so that it stays open when closing many headers at once,
+    /// e.g. with [`Self::collapse_all`] or [`Self::set_subtree_open`].
+    ///
+    /// It can still be closed on its own. See also [`CollapsingHeader::pinnable`].
+    pub fn set_pinned(&mut self, pinned: bool) {
+        self.state.pinned = pinned;
+    }
+
+    /// Use this openness instead of animating between closed and open,
+    /// e.g. to drive the openness from your own animation system.
+    ///
+    /// [`Self::is_open`] still controls the logical state, which is what gets stored.
+    /// While overridden, the built-in animation is kept settled at [`Self::is_open`],
+    /// so release the override when it has reached the target to avoid a jump.
+    ///
+    /// The openness is clamped to `0..=1`, and at 0 the body isn't shown at all.
+    /// `None` restores the built-in animation.
+    pub fn set_openness_override(&mut self, openness: Option<f32>) {
+        self.openness_override = openness;
+    }
+
+    /// While animating, should the body pretend to only be as tall as its visible part?
+    ///
+    /// This is what makes the widgets below the body move smoothly, and is on by default.
+    ///
+    /// Turn it off if you are handling the clipping yourself, e.g. when the body
+    /// is embedded in a container that animates on its own and needs the true size of the body.
+    /// The downside is that the parent will then reserve the full height of the body during the animation.
+    pub fn set_clamp_body_rect(&mut self, clamp_body_rect: bool) {
+        self.clamp_body_rect = clamp_body_rect;
+    }
+
+    /// How the body appears while animating. Default: [`RevealStyle::Clip`].
+    pub fn set_reveal_style(&mut self, reveal_style: RevealStyle) {
+        self.reveal_style = reveal_style;
+    }
+
+    /// Paint the measured body height, the visible height and the openness next to the body,
+    /// and outline the clip rect of the body. Off by default.
+    ///
+    /// This is a development aid for finding problems with the animation.
+    pub fn set_debug(&mut self, debug: bool) {
+        self.debug = debug;
+    }
+
+    /// Is the region open, but still shown as closed because of [`Self::set_pending`]?
+    pub fn is_pending(&self) -> bool {
+        self.pending && self.state.open
+    }
+
+    /// Keep showing an open region as closed, e.g. while its contents are loading.
+    ///
+    /// Once no longer pending, the region animates open as usual.
+    /// This does not change [`Self::is_open`], which is what gets stored.
+    pub fn set_pending(&mut self, pending: bool) {
+        self.pending = pending;
+    }
+
+    /// Finish any ongoing open/close animation of the region with the given id,
+    /// without changing whether it is open.
+    ///
+    /// Use this when swapping out the contents under the same ids (e.g. when loading a new document),
+    /// so that the regions show up fully open or closed instead of animating.
+    ///
+    /// This resets the animation with the given id,
+    /// so pass the [`Self::anim_id`] if you have changed it.
+    pub fn reset_animation(ctx: &Context, id: Id) {
+        let open = Self::load(ctx, id).map_or(false, |state| state.is_open());
+        ctx.animate_bool_with_time(id, open, 0.0);
+    }
+
+    /// 0 for closed, 1 for open, with tweening.
+    ///
+    /// Can briefly be above 1 with [`AnimationStyle::Overshoot`].
+    /// Always 0 or 1 with [`crate::Options::reduce_collapse_animation`].
     pub fn openness(&self, ctx: &Context) -> f32 {
-        if ctx.memory(|mem| mem.everything_is_visible()) {
+        self.openness_with_time(ctx, self.animation_time(ctx))
+    }
+
+    /// Like [`Self::openness`], but animating over `animation_time` seconds,
+    /// regardless of [`Self::set_animation_time`].
+    pub fn openness_with_time(&self, ctx: &Context, animation_time: f32) -> f32 {
+        if self.is_pending() {
+            // Stay settled at closed, so that we animate open once ready:
+            ctx.animate_bool_with_time(self.anim_id, false, 0.0)
+        } else if let Some(openness) = self.openness_override {
+            // Keep the animation settled, so that there is no tween when the override is released:
+            ctx.animate_bool_with_time(self.anim_id, self.state.open, 0.0);
+            openness.clamp(0.0, 1.0)
+        } else if ctx.memory(|mem| mem.everything_is_visible()) || Self::measuring(ctx, self.id) {
             1.0
+        } else if ctx.options(|options| options.reduce_collapse_animation) {
+            // Keep the animation settled, so that there is no tween if the option is turned off:
+            ctx.animate_bool_with_time(self.anim_id, self.state.open, 0.0);
+            if self.state.open {
+                1.0
+            } else {
+                0.0
+            }
+        } else if self
+            .state
+            .open_height
+            .map_or(false, |height| height < self.min_animate_height)
+        {
+            // Too short to be worth animating:
+            ctx.animate_bool_with_time(self.anim_id, self.state.open, 0.0)
+        } else if self.animation == AnimationStyle::Responsive {
+            ctx.animate_bool_with_time_and_easing(
+                self.anim_id,
+                self.state.open,
+                animation_time,
+                emath::easing::cubic_out,
+            )
         } else {
-            ctx.animate_bool_responsive(self.id, self.state.open)
+            let t = ctx.animate_bool_with_time(self.anim_id, self.state.open, animation_time);
+            let segment_id = self.anim_id.with("ease_segment");
+            self.animation.ease(ctx, segment_id, self.state.open, t)
+        }
+    }
+
+    /// How many seconds until the open/close animation is done, or 0 if it is settled.
+    ///
+    /// Use this to schedule what should happen after the animation,
+    /// e.g. open the next section once this one is fully open.
+    /// Always 0 while [`Self::set_pending`] or [`Self::set_openness_override`] is in effect.
+    pub fn animation_remaining(&self, ctx: &Context) -> f32 {
+        if self.is_pending() || self.openness_override.is_some() {
+            return 0.0;
+        }
+        // The eased openness is based on this linear progress:
+        let animation_time = self.animation_time(ctx);
+        let progress = ctx.animate_bool_with_time(self.anim_id, self.state.open, animation_time);
+        let target = if self.state.open { 1.0 } else { 0.0 };
+        (target - progress).abs() * animation_time
+    }
+
+    /// Animate towards this height of the body, until it has been shown and its real height is known.
+    ///
+    /// This avoids the small placeholder height used the first time the region opens.
+    /// Has no effect once the height has been measured. See also [`Self::measure_open_height`].
+    ///
+    /// If the body turns out taller or shorter, the measured height replaces this one.
+    /// See [`CollapsingHeader::known_open_height`] and [`CollapsingHeader::estimated_body_height`].
+    pub fn set_estimated_open_height(&mut self, height: f32) {
+        if self.state.open_height.is_none() {
+            self.state.open_height = Some(height);
+        }
+    }
+
+    /// Lay out the body invisibly, without taking up any space, to learn how tall it is when open.
+    ///
+    /// Without this, the first time the region opens it animates towards a placeholder height,
+    /// and then jumps to the real one.
+    /// The height is remembered (the state is [`Self::store`]d),
+    /// and returned, but [`Self::is_open`] is not changed.
+    ///
+    /// `add_body` is called an extra time, so beware of side effects.
+    /// The body is laid out at the width available in `ui`,
+    /// so call this where the body would be shown, e.g. inside [`Ui::indent`].
+    pub fn measure_open_height<R>(
+        &mut self,
+        ui: &mut Ui,
+        add_body: impl FnOnce(&mut Ui) -> R,
+    ) -> f32 {
+        let height = show_unmounted(ui, add_body);
+        self.state.open_height = Some(height);
+        self.store(ui.ctx());
+        height
+    }
+
+    /// Show the header with the id `root`, and all headers inside it, as fully open while
+    /// running `add_contents`, without changing whether they are open.
+    ///
+    /// Use this for a measuring pass, e.g. to size a panel to fit all of the contents:
+    /// afterwards [`Self::full_content_height`] of each of the headers is known.
+    /// You probably want to show the contents in an invisible [`Ui`] (see [`Ui::set_invisible`]).
+    pub fn measure_expanded<R>(
+        ctx: &Context,
+        root: Id,
+        add_contents: impl FnOnce(&Context) -> R,
+    ) -> R {
+        let roots_id = Self::measure_roots_id();
+        ctx.data_mut(|d| d.get_temp_mut_or_default::<Vec<Id>>(roots_id).push(root));
+        let ret = add_contents(ctx);
+        ctx.data_mut(|d| {
+            let roots = d.get_temp_mut_or_default::<Vec<Id>>(roots_id);
+            if let Some(index) = roots.iter().rposition(|&id| id == root) {
+                roots.remove(index);
+            }
+        });
+        ret
+    }
+
+    fn measure_roots_id() -> Id {
+        Id::new("collapsing_measure_expanded")
+    }
+
+    /// Is the header with this id inside a [`Self::measure_expanded`] pass?
+    fn measuring(ctx: &Context, id: Id) -> bool {
+        let roots = ctx.data(|d| d.get_temp::<Vec<Id>>(Self::measure_roots_id()));
+        match roots {
+            Some(roots) if !roots.is_empty() => {
+                roots.contains(&id)
+                    || HeaderRegistry::read(ctx, |registry| {
+                        registry.parents.iter().any(|parent| roots.contains(parent))
+                    })
+            }
+            _ => false,
         }
     }
 
@@ -83,9 +840,10 @@ impl CollapsingState {
         button_size: Vec2,
     ) -> Response {
         let (_id, rect) = ui.allocate_space(button_size);
-        let response = ui.interact(rect, self.id, Sense::click());
+        let mut response = ui.interact(rect, self.id, Sense::click());
         if response.clicked() {
             self.toggle(ui);
+            response.mark_changed();
         }
         let openness = self.openness(ui.ctx());
         paint_default_icon(ui, openness, &response);
@@ -94,6 +852,15 @@ impl CollapsingState {
 
     /// Will toggle when clicked, etc.
     fn show_default_button_indented(&mut self, ui: &mut Ui) -> Response {
+        self.show_button_indented(ui, paint_default_icon).response
+    }
+
+    /// Like [`Self::show_toggle_button`] with the default icon,
+    /// but also returns the openness that was used to paint the icon.
+    ///
+    /// Use this when building a custom header so that the rest of the header
+    /// can be animated with the exact same openness as the button.
+    pub fn show_default_toggle_button(&mut self, ui: &mut Ui) -> ToggleButtonResponse {
         self.show_button_indented(ui, paint_default_icon)
     }
 
@@ -102,23 +869,25 @@ impl CollapsingState {
         &mut self,
         ui: &mut Ui,
         icon_fn: impl FnOnce(&mut Ui, f32, &Response) + 'static,
-    ) -> Response {
+    ) -> ToggleButtonResponse {
         let size = vec2(ui.spacing().indent, ui.spacing().icon_width);
         let (_id, rect) = ui.allocate_space(size);
-        let response = ui.interact(rect, self.id, Sense::click());
+        let mut response = ui.interact(rect, self.id, Sense::click());
         if response.clicked() {
             self.toggle(ui);
+            response.mark_changed();
         }
 
         let (mut icon_rect, _) = ui.spacing().icon_rectangles(response.rect);
-        icon_rect.set_center(pos2(
-            response.rect.left() + ui.spacing().indent / 2.0,
-            response.rect.center().y,
-        ));
+        let icon_x = match self.icon_side {
+            HeaderIconSide::Left => response.rect.left() + ui.spacing().indent / 2.0,
+            HeaderIconSide::Right => response.rect.right() - ui.spacing().icon_width / 2.0,
+        };
+        icon_rect.set_center(pos2(icon_x, response.rect.center().y));
         let openness = self.openness(ui.ctx());
         let small_icon_response = response.clone().with_new_rect(icon_rect);
         icon_fn(ui, openness, &small_icon_response);
-        response
+        ToggleButtonResponse { response, openness }
     }
 
     /// Shows header and body (if expanded).
@@ -140,16 +909,52 @@ impl CollapsingState {
     /// # });
     /// ```
     pub fn show_header<HeaderRet>(
-        mut self,
+        self,
         ui: &mut Ui,
         add_header: impl FnOnce(&mut Ui) -> HeaderRet,
     ) -> HeaderResponse<'_, HeaderRet> {
+        self.show_header_animated(ui, |ui, _openness| add_header(ui))
+    }
+
+    /// Like [`Self::show_header`], but `add_header` is also given the [`Self::openness`],
+    /// so that the header can animate in sync with the icon and the body.
+    ///
+    /// ```
+    /// # egui::__run_test_ui(|ui| {
+    /// let id = ui.make_persistent_id("my_animated_header");
+    /// egui::collapsing_header::CollapsingState::load_with_default_open(ui.ctx(), id, false)
+    ///     .show_header_animated(ui, |ui, openness| {
+    ///         // Highlight the label as the header opens:
+    ///         let visuals = ui.visuals();
+    ///         let color = visuals
+    ///             .weak_text_color()
+    ///             .lerp_to_gamma(visuals.strong_text_color(), openness);
+    ///         ui.colored_label(color, "Header");
+    ///     })
+    ///     .body(|ui| ui.label("Body"));
+    /// # });
+    /// ```
+    pub fn show_header_animated<HeaderRet>(
+        mut self,
+        ui: &mut Ui,
+        add_header: impl FnOnce(&mut Ui, f32) -> HeaderRet,
+    ) -> HeaderResponse<'_, HeaderRet> {
+        let openness = self.openness(ui.ctx());
         let header_response = ui.horizontal(|ui| {
+            if self.icon_side == HeaderIconSide::Right {
+                let header = add_header(ui, openness);
+                let collapser = ui
+                    .with_layout(Layout::right_to_left(Align::Center), |ui| {
+                        self.show_default_button_indented(ui)
+                    })
+                    .inner;
+                return (collapser, header);
+            }
             let prev_item_spacing = ui.spacing_mut().item_spacing;
             ui.spacing_mut().item_spacing.x = 0.0; // the toggler button uses the full indent width
             let collapser = self.show_default_button_indented(ui);
             ui.spacing_mut().item_spacing = prev_item_spacing;
-            (collapser, add_header(ui))
+            (collapser, add_header(ui, openness))
         });
         HeaderResponse {
             state: self,
@@ -162,6 +967,117 @@ impl CollapsingState {
         }
     }
 
+    /// Like [`Self::show_header`], but clicking anywhere on the header row toggles, not just the button.
+    ///
+    /// Clicks on interactive widgets in the header, like buttons or text edits, go to those widgets and don't toggle.
+    /// Labels in the header aren't selectable, so that clicking them toggles.
+    /// The returned header response senses the clicks on the row.
+    ///
+    /// ```
+    /// # egui::__run_test_ui(|ui| {
+    /// let id = ui.make_persistent_id("my_full_click_header");
+    /// egui::collapsing_header::CollapsingState::load_with_default_open(ui.ctx(), id, false)
+    ///     .show_header_full_click(ui, |ui| {
+    ///         ui.label("Header");
+    ///         if ui.button("Reset").clicked() {
+    ///             // …
+    ///         }
+    ///     })
+    ///     .body(|ui| ui.label("Body"));
+    /// # });
+    /// ```
+    pub fn show_header_full_click<HeaderRet>(
+        self,
+        ui: &mut Ui,
+        add_header: impl FnOnce(&mut Ui) -> HeaderRet,
+    ) -> HeaderResponse<'_, HeaderRet> {
+        let row = RowBehind::begin(ui, self.id.with("full_click_row"), Sense::click());
+
+        let mut header = self.show_header(ui, |ui| {
+            ui.style_mut().interaction.selectable_labels = false;
+            add_header(ui)
+        });
+        let row_rect = header.header_response.response.rect;
+        let row_response = row.end(header.ui, row_rect);
+        if row_response.clicked() {
+            header.state.toggle(header.ui);
+            header.toggle_button_response.mark_changed();
+        }
+        header.header_response.response = row_response;
+        header
+    }
+
+    /// Like [`Self::show_header`], but the header row can be clicked to select it,
+    /// and is highlighted when `selected`, like a [`crate::SelectableLabel`].
+    ///
+    /// Clicking the toggle button still only toggles.
+    /// Returns the response of the whole row, so check [`Response::clicked`] to select it.
+    ///
+    /// ```
+    /// # egui::__run_test_ui(|ui| {
+    /// # let mut selected = false;
+    /// let id = ui.make_persistent_id("my_selectable_header");
+    /// let (row, header) =
+    ///     egui::collapsing_header::CollapsingState::load_with_default_open(ui.ctx(), id, false)
+    ///         .show_header_selectable(ui, selected, |ui| ui.label("Header"));
+    /// header.body(|ui| ui.label("Body"));
+    /// if row.clicked() {
+    ///     selected = true;
+    /// }
+    /// # });
+    /// ```
+    pub fn show_header_selectable<HeaderRet>(
+        self,
+        ui: &mut Ui,
+        selected: bool,
+        add_header: impl FnOnce(&mut Ui) -> HeaderRet,
+    ) -> (Response, HeaderResponse<'_, HeaderRet>) {
+        let row = RowBehind::begin(ui, self.id.with("selectable_row"), Sense::click());
+        let background = ui.painter().add(Shape::Noop);
+
+        let header = self.show_header(ui, add_header);
+        let row_rect = header
+            .header_response
+            .response
+            .rect
+            .with_max_x(header.ui.max_rect().right());
+        let row_response = row.end(header.ui, row_rect);
+        if header.ui.is_rect_visible(row_rect)
+            && (selected || row_response.hovered() || row_response.has_focus())
+        {
+            let visuals = header
+                .ui
+                .style()
+                .interact_selectable(&row_response, selected);
+            header.ui.painter().set(
+                background,
+                epaint::RectShape::new(
+                    row_rect.expand(visuals.expansion),
+                    visuals.rounding,
+                    visuals.bg_fill,
+                    visuals.bg_stroke,
+                ),
+            );
+        }
+        (row_response, header)
+    }
+
+    /// Show the header and then the body, i.e. [`Self::show_header`] followed by [`HeaderResponse::body`].
+    ///
+    /// The same [`CollapsingState`] is used for both collapsing headers and [`crate::Window`]s,
+    /// so this works for either.
+    ///
+    /// Returns the response of the header row and the body.
+    pub fn show_region<R>(
+        self,
+        ui: &mut Ui,
+        add_header: impl FnOnce(&mut Ui),
+        add_body: impl FnOnce(&mut Ui) -> R,
+    ) -> (Response, Option<InnerResponse<R>>) {
+        let (_, header_response, body_response) = self.show_header(ui, add_header).body(add_body);
+        (header_response.response, body_response)
+    }
+
     /// Show body if we are open, with a nice animation between closed and open.
     /// Indent the body to show it belongs to the header.
     ///
@@ -174,6 +1090,13 @@ impl CollapsingState {
     ) -> Option<InnerResponse<R>> {
         let id = self.id;
         self.show_body_unindented(ui, |ui| {
+            if ui.layout().horizontal_align() == Align::Max {
+                // Right-to-left:
+                return indent_from_right(ui, id, |ui| {
+                    ui.expand_to_include_x(header_response.rect.left());
+                    add_body(ui)
+                });
+            }
             ui.indent(id, |ui| {
                 // make as wide as the header:
                 ui.expand_to_include_x(header_response.rect.right());
@@ -191,9 +1114,13 @@ impl CollapsingState {
         add_body: impl FnOnce(&mut Ui) -> R,
     ) -> Option<InnerResponse<R>> {
         let openness = self.openness(ui.ctx());
-        if openness <= 0.0 {
+        let mut debug_clip = ui.clip_rect();
+        let mut debug_max_height = None;
+        let ret_response = if openness <= 0.0 {
             self.store(ui.ctx()); // we store any earlier toggling as promised in the docstring
             None
+        } else if openness < 1.0 && self.horizontal {
+            Some(self.show_body_sideways(ui, openness, add_body))
         } else if openness < 1.0 {
             Some(ui.scope(|child_ui| {
                 let max_height = if self.state.open && self.state.open_height.is_none() {
@@ -206,9 +1133,45 @@ impl CollapsingState {
                     remap_clamp(openness, 0.0..=1.0, 0.0..=full_height)
                 };
 
-                let mut clip_rect = child_ui.clip_rect();
-                clip_rect.max.y = clip_rect.max.y.min(child_ui.max_rect().top() + max_height);
+                let top = child_ui.max_rect().top();
+                // Narrow the incoming clip rather than replacing it,
+                // so a body nested in an animating parent stays inside the parent's clip:
+                let clip_rect = child_ui
+                    .clip_rect()
+                    .intersect(Rect::everything_above(top + max_height));
                 child_ui.set_clip_rect(clip_rect);
+                debug_clip = clip_rect;
+                debug_max_height = Some(max_height);
+
+                if self.reveal_style == RevealStyle::Slide {
+                    // Move the body up behind the header, by how much of it is hidden:
+                    let offset = match self.state.open_height {
+                        Some(full_height) => max_height - full_height,
+                        None => 0.0,
+                    };
+                    let max_rect = child_ui.max_rect().translate(vec2(0.0, offset));
+                    let mut content_ui = child_ui.child_ui(max_rect, *child_ui.layout(), None);
+                    content_ui.set_clip_rect(clip_rect.intersect(Rect::everything_below(top)));
+
+                    let ret = add_body(&mut content_ui);
+
+                    let content_rect = content_ui.min_rect();
+                    self.state.open_height = Some(content_rect.height());
+                    self.store(child_ui.ctx()); // remember the height
+
+                    // Only the visible part takes up space, unless asked to report it all:
+                    let height = if self.clamp_body_rect {
+                        content_rect.height().at_most(max_height)
+                    } else {
+                        content_rect.height()
+                    };
+                    let rect = Rect::from_min_size(
+                        pos2(content_rect.left(), top),
+                        vec2(content_rect.width(), height),
+                    );
+                    child_ui.allocate_rect(rect, Sense::hover());
+                    return ret;
+                }
 
                 let ret = add_body(child_ui);
 
@@ -216,18 +1179,95 @@ impl CollapsingState {
                 self.state.open_height = Some(min_rect.height());
                 self.store(child_ui.ctx()); // remember the height
 
-                // Pretend children took up at most `max_height` space:
-                min_rect.max.y = min_rect.max.y.at_most(min_rect.top() + max_height);
-                child_ui.force_set_min_rect(min_rect);
+                if self.fade_edge > 0.0 && min_rect.height() > max_height {
+                    let bottom = top + max_height;
+                    let fade_rect = Rect::from_x_y_ranges(
+                        min_rect.x_range(),
+                        (bottom - self.fade_edge).at_least(top)..=bottom,
+                    );
+                    paint_fade_out(child_ui, fade_rect);
+                }
+
+                if self.clamp_body_rect {
+                    // Pretend children took up at most `max_height` space:
+                    min_rect.max.y = min_rect.max.y.at_most(min_rect.top() + max_height);
+                    child_ui.force_set_min_rect(min_rect);
+                }
                 ret
             }))
         } else {
             let ret_response = ui.scope(add_body);
             let full_size = ret_response.response.rect.size();
             self.state.open_height = Some(full_size.y);
+            if self.horizontal {
+                self.state.open_width = Some(full_size.x);
+            }
             self.store(ui.ctx()); // remember the height
             Some(ret_response)
+        };
+
+        if let (true, Some(ret_response)) = (self.debug, &ret_response) {
+            let painter = ui.ctx().debug_painter();
+            let color = Color32::from_rgb(255, 0, 255);
+            painter.rect_stroke(debug_clip, 0.0, (1.0, color));
+            let fmt_height = |height: Option<f32>| match height {
+                Some(height) => format!("{height:.1}"),
+                None => "-".to_owned(),
+            };
+            let text = format!(
+                "open_height: {}\nmax_height: {}\nopenness: {openness:.3}",
+                fmt_height(self.state.open_height),
+                fmt_height(debug_max_height),
+            );
+            painter.debug_text(
+                ret_response.response.rect.right_top(),
+                Align2::LEFT_TOP,
+                color,
+                text,
+            );
         }
+
+        ret_response
+    }
+
+    /// Show the body while animating, revealing it from the left, see [`Self::set_horizontal`].
+    fn show_body_sideways<R>(
+        &mut self,
+        ui: &mut Ui,
+        openness: f32,
+        add_body: impl FnOnce(&mut Ui) -> R,
+    ) -> InnerResponse<R> {
+        ui.scope(|child_ui| {
+            let max_width = if self.state.open && self.state.open_width.is_none() {
+                // First frame of expansion.
+                // We don't know full width yet, but we will next frame.
+                10.0
+            } else {
+                let full_width = self.state.open_width.unwrap_or_default();
+                remap_clamp(openness, 0.0..=1.0, 0.0..=full_width)
+            };
+
+            let left = child_ui.max_rect().left();
+            // Like the vertical animation, stay inside the clip of an animating parent:
+            let clip_rect = child_ui
+                .clip_rect()
+                .intersect(Rect::everything_left_of(left + max_width));
+            child_ui.set_clip_rect(clip_rect);
+
+            let ret = add_body(child_ui);
+
+            let mut min_rect = child_ui.min_rect();
+            self.state.open_width = Some(min_rect.width());
+            self.state.open_height = Some(min_rect.height());
+            self.store(child_ui.ctx()); // remember the width
+
+            if self.clamp_body_rect {
+                // Pretend children took up at most `max_width` space:
+                min_rect.max.x = min_rect.max.x.at_most(min_rect.left() + max_width);
+                child_ui.force_set_min_rect(min_rect);
+            }
+            ret
+        })
     }
 
     /// Paint this [`CollapsingState`]'s toggle button. Takes an [`IconPainter`] as the icon.
@@ -258,7 +1298,221 @@ impl CollapsingState {
         ui: &mut Ui,
         icon_fn: impl FnOnce(&mut Ui, f32, &Response) + 'static,
     ) -> Response {
-        self.show_button_indented(ui, icon_fn)
+        self.show_button_indented(ui, icon_fn).response
+    }
+}
+
+/// From [`CollapsingState::show_default_toggle_button`].
+#[derive(Clone, Debug)]
+pub struct ToggleButtonResponse {
+    /// Response of the toggle button.
+    pub response: Response,
+
+    /// The openness used to paint the button this frame.
+    ///
+    /// 0 for closed, 1 for open, with tweening.
+    pub openness: f32,
+}
+
+/// Keeps track of which [`CollapsingHeader`]s are shown inside which, in draw order.
+#[derive(Clone, Default)]
+struct HeaderRegistry {
+    frame_nr: u64,
+
+    /// The headers whose bodies are currently being shown, innermost last.
+    parents: Vec<Id>,
+
+    /// The children of each header shown this frame, in draw order.
+    /// Top-level headers are stored under [`Id::NULL`].
+    children: IdMap<Vec<Id>>,
+
+    /// [`Self::children`] of the previous frame.
+    prev_children: IdMap<Vec<Id>>,
+
+    /// [`Self::prev_children`] of the frame before,
+    /// so that [`CollapsingState::retain`] also finds the headers that were gone last frame.
+    older_children: IdMap<Vec<Id>>,
+
+    /// The [`CollapsingGroup`]s currently being shown, innermost last.
+    groups: Vec<Id>,
+
+    /// The headers shown in each [`CollapsingGroup`] this frame, in draw order.
+    group_headers: IdMap<Vec<GroupedHeader>>,
+}
+
+#[derive(Clone)]
+struct GroupedHeader {
+    id: Id,
+    label: String,
+    rect: Rect,
+
+    /// The leftmost x where the value of the header can be shown, if it has one.
+    value_x: Option<f32>,
+}
+
+impl HeaderRegistry {
+    fn write<R>(ctx: &Context, writer: impl FnOnce(&mut Self) -> R) -> R {
+        let frame_nr = ctx.frame_nr();
+        ctx.data_mut(|d| {
+            let registry = d.get_temp_mut_or_default::<Self>(Id::NULL);
+            if registry.frame_nr != frame_nr {
+                registry.end_frame();
+                registry.frame_nr = frame_nr;
+            }
+            writer(registry)
+        })
+    }
+
+    fn end_frame(&mut self) {
+        let mut children = std::mem::take(&mut self.children);
+
+        // Remember the children of headers that were shown, but closed:
+        let shown: Vec<Id> = children.values().flatten().copied().collect();
+        for id in shown {
+            Self::carry_over(&self.prev_children, &mut children, id);
+        }
+
+        self.older_children = std::mem::replace(&mut self.prev_children, children);
+        self.parents.clear();
+        self.groups.clear();
+        self.group_headers.clear();
+    }
+
+    fn carry_over(old: &IdMap<Vec<Id>>, new: &mut IdMap<Vec<Id>>, id: Id) {
+        if new.contains_key(&id) {
+            return;
+        }
+        if let Some(children) = old.get(&id) {
+            new.insert(id, children.clone());
+            for &child in children {
+                Self::carry_over(old, new, child);
+            }
+        }
+    }
+
+    /// All headers inside `root`, recursively, as of the previous frame.
+    fn descendants(&self, root: Id) -> Vec<Id> {
+        let mut descendants = vec![];
+        let mut stack = vec![root];
+        while let Some(id) = stack.pop() {
+            for &child in self.prev_children.get(&id).into_iter().flatten() {
+                if child != root && !descendants.contains(&child) {
+                    descendants.push(child);
+                    stack.push(child);
+                }
+            }
+        }
+        descendants
+    }
+
+    /// The headers shown inside `parent` during the last two frames, without duplicates.
+    fn recent_children(&self, parent: Id) -> Vec<Id> {
+        let mut recent = vec![];
+        for children in [&self.older_children, &self.prev_children, &self.children] {
+            for &child in children.get(&parent).into_iter().flatten() {
+                if child != parent && !recent.contains(&child) {
+                    recent.push(child);
+                }
+            }
+        }
+        recent
+    }
+
+    /// Forget that the header with the given id was shown, e.g. when its state is removed.
+    fn forget(&mut self, id: Id) {
+        for children in [
+            &mut self.older_children,
+            &mut self.prev_children,
+            &mut self.children,
+        ] {
+            children.remove(&id);
+            for siblings in children.values_mut() {
+                siblings.retain(|&child| child != id);
+            }
+        }
+    }
+
+    /// Reading needs write access too, so that a new frame can be started.
+    fn read<R>(ctx: &Context, reader: impl FnOnce(&Self) -> R) -> R {
+        Self::write(ctx, |registry| reader(registry))
+    }
+
+    /// Register a header shown this frame, as a child of the innermost body being shown.
+    fn register(ctx: &Context, id: Id, label: &str, rect: Rect, value_x: Option<f32>) {
+        Self::register_child(ctx, id);
+        Self::write(ctx, |registry| {
+            if let Some(group) = registry.groups.last() {
+                registry
+                    .group_headers
+                    .entry(*group)
+                    .or_default()
+                    .push(GroupedHeader {
+                        id,
+                        label: label.to_owned(),
+                        rect,
+                        value_x,
+                    });
+            }
+        });
+    }
+
+    /// Like [`Self::register`], but not as part of any [`CollapsingGroup`].
+    fn register_child(ctx: &Context, id: Id) {
+        Self::write(ctx, |registry| {
+            let parent = registry.parents.last().copied().unwrap_or(Id::NULL);
+            registry.children.entry(parent).or_default().push(id);
+        });
+    }
+
+    /// Where the innermost [`CollapsingGroup::align_values`] group wants the values to be shown.
+    fn value_column(ctx: &Context) -> Option<f32> {
+        let group = Self::read(ctx, |registry| registry.groups.last().copied())?;
+        ctx.data(|d| d.get_temp(group.with("value_column")))
+    }
+
+    /// Call when starting to show the body of the header with the given id.
+    fn push_parent(ctx: &Context, id: Id) {
+        Self::write(ctx, |registry| {
+            registry.children.entry(id).or_default();
+            registry.parents.push(id);
+        });
+    }
+
+    /// Call when done showing the body of a header.
+    fn pop_parent(ctx: &Context) {
+        Self::write(ctx, |registry| registry.parents.pop());
+    }
+}
+
+/// Senses a header row behind the widgets in it, so that those are on top and get their own clicks.
+///
+/// The row is sensed once, before its widgets, with its rect from the previous frame
+/// (like [`CollapsingHeader::sense_body_background`]).
+/// On the first frame it is sensed after them instead.
+struct RowBehind {
+    id: Id,
+    sense: Sense,
+    response: Option<Response>,
+}
+
+impl RowBehind {
+    fn begin(ui: &Ui, id: Id, sense: Sense) -> Self {
+        let rect = ui.data(|d| d.get_temp::<Rect>(id));
+        let response = rect.map(|rect| ui.interact(rect, id, sense));
+        Self {
+            id,
+            sense,
+            response,
+        }
+    }
+
+    /// Call once the rect of the row is known.
+    fn end(self, ui: &Ui, rect: Rect) -> Response {
+        ui.data_mut(|d| d.insert_temp(self.id, rect));
+        match self.response {
+            Some(response) => response.with_new_rect(rect),
+            None => ui.interact(rect, self.id, self.sense),
+        }
     }
 }
 
@@ -284,6 +1538,13 @@ impl<'ui, HeaderRet> HeaderResponse<'ui, HeaderRet> {
         self.state.toggle(self.ui);
     }
 
+    /// Was the toggle button clicked this frame, opening or closing the region?
+    ///
+    /// Same as [`Response::changed`] of the toggle button.
+    pub fn toggled(&self) -> bool {
+        self.toggle_button_response.changed()
+    }
+
     /// Returns the response of the collapsing button, the custom header, and the custom body.
     pub fn body<BodyRet>(
         mut self,
@@ -325,30 +1586,323 @@ impl<'ui, HeaderRet> HeaderResponse<'ui, HeaderRet> {
 
 /// Paint the arrow icon that indicated if the region is open or not
 pub fn paint_default_icon(ui: &mut Ui, openness: f32, response: &Response) {
+    paint_default_icon_scaled(ui, openness, response, 0.75..=0.75);
+}
+
+/// Like [`paint_default_icon`], but also grows (or shrinks) the arrow as it opens.
+///
+/// The arrow is `scale_range.start()` times the size of the response rect when closed,
+/// and `scale_range.end()` times it when open.
+/// [`paint_default_icon`] uses `0.75` for both.
+pub fn paint_default_icon_scaled(
+    ui: &Ui,
+    openness: f32,
+    response: &Response,
+    scale_range: RangeInclusive<f32>,
+) {
+    paint_arrow_icon(
+        ui,
+        openness,
+        response,
+        scale_range,
+        None,
+        ArrowTurn::RightToDown,
+    );
+}
+
+/// Like [`paint_default_icon_scaled`], but always in `color`, instead of following the interaction state.
+pub fn paint_default_icon_styled(
+    ui: &Ui,
+    openness: f32,
+    response: &Response,
+    scale_range: RangeInclusive<f32>,
+    color: Color32,
+) {
+    let turn = ArrowTurn::RightToDown;
+    paint_arrow_icon(ui, openness, response, scale_range, Some(color), turn);
+}
+
+/// Which way [`paint_arrow_icon`] points when closed and when open.
+#[derive(Clone, Copy)]
+enum ArrowTurn {
+    /// The default.
+    RightToDown,
+
+    /// For a right-to-left layout.
+    LeftToDown,
+
+    /// For a body to the right of the header.
+    DownToRight,
+}
+
+/// The default arrow, rotating as the region opens.
+fn paint_arrow_icon(
+    ui: &Ui,
+    openness: f32,
+    response: &Response,
+    scale_range: RangeInclusive<f32>,
+    color: Option<Color32>,
+    turn: ArrowTurn,
+) {
     let visuals = ui.style().interact(response);
 
     let rect = response.rect;
+    let scale = lerp(scale_range, openness);
 
     // Draw a pointy triangle arrow:
-    let rect = Rect::from_center_size(rect.center(), vec2(rect.width(), rect.height()) * 0.75);
+    let rect = Rect::from_center_size(rect.center(), vec2(rect.width(), rect.height()) * scale);
     let rect = rect.expand(visuals.expansion);
     let mut points = vec![rect.left_top(), rect.right_top(), rect.center_bottom()];
     use std::f32::consts::TAU;
-    let rotation = emath::Rot2::from_angle(remap(openness, 0.0..=1.0, -TAU / 4.0..=0.0));
+    let angle_range = match turn {
+        ArrowTurn::RightToDown => -TAU / 4.0..=0.0,
+        ArrowTurn::LeftToDown => TAU / 4.0..=0.0,
+        ArrowTurn::DownToRight => 0.0..=-TAU / 4.0,
+    };
+    let rotation = emath::Rot2::from_angle(remap(openness, 0.0..=1.0, angle_range));
     for p in &mut points {
         *p = rect.center() + rotation * (*p - rect.center());
     }
 
-    ui.painter().add(Shape::convex_polygon(
-        points,
-        visuals.fg_stroke.color,
-        Stroke::NONE,
-    ));
+    let color = color.unwrap_or(visuals.fg_stroke.color);
+    ui.painter()
+        .add(Shape::convex_polygon(points, color, Stroke::NONE));
+}
+
+/// Paint a down-chevron when closed and an up-chevron when open, cross-fading between them.
+///
+/// Used by [`CollapsingIcon::ChevronSwap`].
+pub fn paint_chevron_swap_icon(ui: &Ui, openness: f32, response: &Response) {
+    let visuals = ui.style().interact(response);
+    let openness = openness.clamp(0.0, 1.0);
+
+    let rect = Rect::from_center_size(response.rect.center(), response.rect.size() * 0.75);
+    let rect = rect.expand(visuals.expansion);
+    let half_height = rect.height() / 4.0;
+    let (top, bottom) = (rect.center().y - half_height, rect.center().y + half_height);
+    let down = vec![
+        pos2(rect.left(), top),
+        pos2(rect.center().x, bottom),
+        pos2(rect.right(), top),
+    ];
+    let up = vec![
+        pos2(rect.left(), bottom),
+        pos2(rect.center().x, top),
+        pos2(rect.right(), bottom),
+    ];
+
+    for (points, opacity) in [(down, 1.0 - openness), (up, openness)] {
+        if 0.0 < opacity {
+            let mut stroke = visuals.fg_stroke;
+            stroke.color = stroke.color.gamma_multiply(opacity);
+            ui.painter().add(Shape::line(points, stroke));
+        }
+    }
+}
+
+/// Paint an image as the icon, fitted into the icon rect while keeping its aspect ratio.
+///
+/// The image is `scale_range.start()` times the fitted size when closed,
+/// and `scale_range.end()` times it when open.
+/// See [`CollapsingHeader::image_icon`].
+pub fn paint_image_icon(
+    ui: &Ui,
+    texture_id: TextureId,
+    image_size: Vec2,
+    openness: f32,
+    response: &Response,
+    scale_range: RangeInclusive<f32>,
+) {
+    let rect = response.rect;
+    if image_size.x <= 0.0 || image_size.y <= 0.0 {
+        return;
+    }
+    let fit = (rect.width() / image_size.x).min(rect.height() / image_size.y);
+    let size = image_size * fit * lerp(scale_range, openness);
+    let uv = Rect::from_min_max(pos2(0.0, 0.0), pos2(1.0, 1.0));
+    ui.painter().image(
+        texture_id,
+        Rect::from_center_size(rect.center(), size),
+        uv,
+        Color32::WHITE,
+    );
 }
 
 /// A function that paints an icon indicating if the region is open or not
 pub type IconPainter = Box<dyn FnOnce(&mut Ui, f32, &Response)>;
 
+/// A function that shows the contents of a header row, see [`CollapsingHeader::custom_header`].
+type HeaderContents = Box<dyn FnOnce(&mut Ui, f32, &Response)>;
+
+/// A function that is called with the rect of the shown body, see [`CollapsingHeader::after_body`].
+type BodyOverlay = Box<dyn FnOnce(&mut Ui, Rect)>;
+
+/// The contents of the context menu of a header, see [`CollapsingHeader::context_menu`].
+type HeaderMenu = Box<dyn FnOnce(&mut Ui)>;
+
+/// The text of the footer below a capped body, see [`CollapsingHeader::height_capped_footer`].
+type FooterText = Arc<dyn Fn(f32) -> WidgetText>;
+
+/// Stores the user data of a [`CollapsingHeader`] under its id.
+type UserDataStorer = Box<dyn FnOnce(&Context, Id)>;
+
+/// How to remove each type of user data stored for a header, see [`CollapsingState::set_user_data`].
+#[derive(Clone, Default)]
+struct UserDataRemovers(Vec<(std::any::TypeId, fn(&mut crate::util::IdTypeMap, Id))>);
+
+/// Which of the built-in icons a [`CollapsingHeader`] uses.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CollapsingIcon {
+    /// An arrow that rotates as the header opens. Leaves have no icon.
+    #[default]
+    Arrow,
+
+    /// Like [`Self::Arrow`], but [`CollapsingHeader::leaf`]s show a small dot in the same place,
+    /// which makes it easier to tell groups and leaves apart in a tree.
+    ///
+    /// The leaves then keep the indentation of the arrow.
+    DotForLeaf,
+
+    /// A chevron at the end of the header that points down when closed and up when open.
+    ///
+    /// Instead of rotating, the two chevrons cross-fade as the header opens.
+    /// The title then starts at the left edge. See [`paint_chevron_swap_icon`].
+    ChevronSwap,
+}
+
+/// Which side of a header the open/close icon is on.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum HeaderIconSide {
+    /// Before the title, which is indented to make room for it.
+    #[default]
+    Left,
+
+    /// Flush with the right edge of the header, like in a macOS sidebar.
+    ///
+    /// The title then starts at the left edge.
+    Right,
+}
+
+/// How the body of a collapsing region appears while it opens and closes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RevealStyle {
+    /// The body stays in place, and more of it is revealed from the top down.
+    #[default]
+    Clip,
+
+    /// The body slides down from behind the header, like a roll-down blind.
+    ///
+    /// The bottom of the body is visible first.
+    Slide,
+}
+
+/// How a collapsing region eases between closed and open.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum AnimationStyle {
+    /// Starts fast and slows down towards the end.
+    #[default]
+    Responsive,
+
+    /// When opening, goes slightly past fully open before settling, like a bounce.
+    ///
+    /// `amount` is the tension of the overshoot:
+    /// 0 doesn't overshoot, and 1.7 overshoots by about 10%.
+    /// Closing is the same as [`Self::Responsive`].
+    /// Reversing halfway continues smoothly from the current openness.
+    Overshoot { amount: f32 },
+}
+
+/// Where the current opening or closing of an [`AnimationStyle`] started, see [`AnimationStyle::ease`].
+#[derive(Clone, Copy, Debug)]
+struct EaseSegment {
+    open: bool,
+
+    /// The linear progress and eased openness when it started.
+    from: (f32, f32),
+
+    /// The linear progress and eased openness last time.
+    last: (f32, f32),
+}
+
+impl AnimationStyle {
+    /// The openness at linear progress `t` towards `open`.
+    ///
+    /// If the direction changed before the previous animation finished,
+    /// this continues from the openness it had, rather than jumping to the other curve.
+    /// The segment is remembered in temp data under `segment_id`.
+    fn ease(self, ctx: &Context, segment_id: Id, open: bool, t: f32) -> f32 {
+        let (end_t, end_value) = if open { (1.0, 1.0) } else { (0.0, 0.0) };
+        let start = 1.0 - end_t;
+        let mut segment = ctx
+            .data(|d| d.get_temp::<EaseSegment>(segment_id))
+            .unwrap_or(EaseSegment {
+                open,
+                from: (start, start),
+                last: (start, start),
+            });
+        if segment.open != open {
+            segment.open = open;
+            segment.from = segment.last;
+        }
+
+        let (from_t, from_value) = segment.from;
+        let progress = if from_t == end_t {
+            1.0
+        } else {
+            ((t - from_t) / (end_t - from_t)).clamp(0.0, 1.0)
+        };
+        let eased = if open {
+            self.ease_open(progress)
+        } else {
+            Self::Responsive.ease_open(progress)
+        };
+        let value = from_value + (end_value - from_value) * eased;
+
+        segment.last = (t, value);
+        ctx.data_mut(|d| d.insert_temp(segment_id, segment));
+        value
+    }
+
+    /// The openness at linear progress `t` of opening.
+    fn ease_open(self, t: f32) -> f32 {
+        match self {
+            Self::Responsive => emath::easing::cubic_out(t),
+            Self::Overshoot { amount } => {
+                let t = t - 1.0;
+                1.0 + (amount + 1.0) * t * t * t + amount * t * t
+            }
+        }
+    }
+}
+
+/// Something to do with a [`CollapsingHeader`], see [`CollapsingHeader::display_queue`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CollapsingEvent {
+    /// Open the header, if it isn't already.
+    Expand,
+
+    /// Close the header, if it isn't already.
+    Collapse,
+
+    /// Open the header if it is closed, and close it if it is open.
+    Toggle,
+}
+
+/// Where the body of a [`CollapsingHeader`] in [`CollapsingHeader::chip_mode`] is shown.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChipBody {
+    /// Show the body in a popup floating just below the chip.
+    ///
+    /// The surrounding layout is not affected by the body at all.
+    Popup,
+
+    /// End the current row after the chip and show the body on its own
+    /// full-width row below it.
+    ///
+    /// The chips after this one continue on a new row below the body.
+    BelowRow,
+}
+
 /// A header which can be collapsed/expanded, revealing a contained [`Ui`] region.
 ///
 /// ```
@@ -363,21 +1917,76 @@ pub type IconPainter = Box<dyn FnOnce(&mut Ui, f32, &Response)>;
 /// # });
 /// ```
 ///
+/// In a horizontal layout (e.g. a toolbar made with [`Ui::horizontal`]),
+/// the body is shown to the right of the header, and is revealed from left to right.
+///
+/// In a right-aligned vertical layout (e.g. `Layout::top_down(Align::Max)` for a right-to-left language),
+/// the header is mirrored: the arrow is on the right, pointing left when closed,
+/// and the body is indented from the right.
+///
 /// If you want to customize the header contents, see [`CollapsingState::show_header`].
 #[must_use = "You should call .show()"]
 pub struct CollapsingHeader {
     text: WidgetText,
     default_open: bool,
     open: Option<bool>,
+    open_once: Option<bool>,
     id_source: Id,
     enabled: bool,
+    disabled_reason: Option<WidgetText>,
+    disabled_alpha: Option<f32>,
     selectable: bool,
     selected: bool,
-    show_background: bool,
-    icon: Option<IconPainter>,
+    frame: FrameOptions,
+    icon: IconOptions,
+    animation: AnimationOptions,
+    editable_title: bool,
+    compact: bool,
+    leaf: bool,
+    notify: Option<std::sync::mpsc::Sender<(Id, bool)>>,
+    body: BodyOptions,
+    recursive_click_modifiers: Option<Modifiers>,
+    collapse_if_space_below: Option<f32>,
+    header_sense: Sense,
+    arrow_sense: Sense,
+    clamp_body_rect: bool,
+    chip_mode: Option<ChipBody>,
+    user_data: Option<UserDataStorer>,
+    cache_collapsed_galley: bool,
+    cache_body: bool,
+    lazy: bool,
+    open_when_ready: Option<bool>,
+    non_collapsible: bool,
+    click_toggles: bool,
+    drop_target: bool,
+    debug: bool,
+    custom_header: Option<HeaderContents>,
+    value: Option<WidgetText>,
+    text_align: Align,
+    button_style: bool,
+    scroll_to_body_on_open: Option<Align>,
+    manage_state: bool,
+    persist: bool,
+    estimated_body_height: Option<f32>,
+    flat_nesting: bool,
+    indent: Option<f32>,
+    sense_body_background: bool,
+    display_queue: Option<Arc<Mutex<VecDeque<CollapsingEvent>>>>,
+    modified: bool,
+    highlight_new: Option<f64>,
+    pinnable: bool,
+    flyout: bool,
+    after_body: Option<BodyOverlay>,
+    context_menu: Option<HeaderMenu>,
+    auto_expand_on_growth: bool,
+    keep_mounted: bool,
+    header_body_gap: Option<f32>,
 }
 
 impl CollapsingHeader {
+    /// How much each level of nesting indents the body with [`Self::flat_nesting`].
+    pub const FLAT_NESTING_INDENT: f32 = 6.0;
+
     /// The [`CollapsingHeader`] starts out collapsed unless you call `default_open`.
     ///
     /// The label is used as an [`Id`] source.
@@ -391,14 +2000,91 @@ impl CollapsingHeader {
             text,
             default_open: false,
             open: None,
+            open_once: None,
             id_source,
             enabled: true,
+            disabled_reason: None,
+            disabled_alpha: None,
             selectable: false,
             selected: false,
-            show_background: false,
-            icon: None,
-        }
-    }
+            frame: FrameOptions {
+                show_background: false,
+                fill: None,
+                stroke: None,
+                rounding: None,
+                card: None,
+                rounded_body_clip: None,
+                body_hover_background: None,
+            },
+            icon: IconOptions {
+                painter: None,
+                style: CollapsingIcon::Arrow,
+                side: HeaderIconSide::Left,
+                scale_range: None,
+                color: None,
+                image: None,
+            },
+            animation: AnimationOptions {
+                header_height: false,
+                id: None,
+                openness_override: None,
+                indent: false,
+                reveal_style: RevealStyle::Clip,
+                min_height: 0.0,
+                fade_edge: 0.0,
+                style: AnimationStyle::Responsive,
+                time: None,
+            },
+            editable_title: false,
+            compact: false,
+            leaf: false,
+            notify: None,
+            body: BodyOptions {
+                enabled: true,
+                max_height: None,
+                show_more_footer: None,
+                peek_height: None,
+                peek_more_text: "Show more".into(),
+                busy: false,
+            },
+            recursive_click_modifiers: None,
+            collapse_if_space_below: None,
+            header_sense: Sense::click(),
+            arrow_sense: Sense::click(),
+            clamp_body_rect: true,
+            chip_mode: None,
+            user_data: None,
+            cache_collapsed_galley: false,
+            cache_body: false,
+            lazy: false,
+            open_when_ready: None,
+            non_collapsible: false,
+            click_toggles: true,
+            drop_target: false,
+            debug: false,
+            custom_header: None,
+            value: None,
+            text_align: Align::Min,
+            button_style: false,
+            scroll_to_body_on_open: None,
+            manage_state: true,
+            persist: true,
+            estimated_body_height: None,
+            flat_nesting: false,
+            indent: None,
+            sense_body_background: false,
+            display_queue: None,
+            modified: false,
+            highlight_new: None,
+            pinnable: false,
+            flyout: false,
+            after_body: None,
+            context_menu: None,
+            auto_expand_on_growth: false,
+            keep_mounted: false,
+            header_body_gap: None,
+        }
+    }
 
     /// By default, the [`CollapsingHeader`] is collapsed.
     /// Call `.default_open(true)` to change this.
@@ -419,6 +2105,35 @@ impl CollapsingHeader {
         self
     }
 
+    /// Like [`Self::open`], but only applied on the frame the requested value changes,
+    /// so the user can still open and close the header while you keep passing the same value.
+    ///
+    /// ```
+    /// # egui::__run_test_ui(|ui| {
+    /// # let search_matches = true;
+    /// // Opens when the search starts matching, but can then be closed by the user:
+    /// egui::CollapsingHeader::new("Results")
+    ///     .set_open_once(search_matches.then_some(true))
+    ///     .show(ui, |ui| ui.label("…"));
+    /// # });
+    /// ```
+    #[inline]
+    pub fn set_open_once(mut self, open: Option<bool>) -> Self {
+        self.open_once = open;
+        self
+    }
+
+    /// If `false`, clicking the header or the arrow doesn't open or close it. Default: `true`.
+    ///
+    /// The clicks are still reported in [`CollapsingResponse::header_response`]
+    /// and [`CollapsingResponse::toggle_response`], e.g. for selection,
+    /// and the header can still be opened and closed with [`Self::open`].
+    #[inline]
+    pub fn click_toggles(mut self, click_toggles: bool) -> Self {
+        self.click_toggles = click_toggles;
+        self
+    }
+
     /// Explicitly set the source of the [`Id`] of this widget, instead of using title label.
     /// This is useful if the title label is dynamic or not unique.
     #[inline]
@@ -436,6 +2151,28 @@ impl CollapsingHeader {
         self
     }
 
+    /// Show this text when hovering the header while it is disabled,
+    /// e.g. to explain why the section is locked.
+    ///
+    /// See [`Self::enabled`] and [`Response::on_disabled_hover_text`].
+    #[inline]
+    pub fn disabled_reason(mut self, reason: impl Into<WidgetText>) -> Self {
+        self.disabled_reason = Some(reason.into());
+        self
+    }
+
+    /// Multiply the opacity of the header and body with this value when disabled,
+    /// in addition to the normal gray-out of disabled widgets.
+    ///
+    /// `alpha` is clamped to `0.0..=1.0`. Default: `None` (no extra fading).
+    ///
+    /// See [`Self::enabled`] and [`Ui::multiply_opacity`].
+    #[inline]
+    pub fn disabled_alpha(mut self, alpha: f32) -> Self {
+        self.disabled_alpha = Some(alpha.clamp(0.0, 1.0));
+        self
+    }
+
     /// Should the [`CollapsingHeader`] show a background behind it? Default: `false`.
     ///
     /// To show it behind all [`CollapsingHeader`] you can just use:
@@ -446,7 +2183,139 @@ impl CollapsingHeader {
     /// ```
     #[inline]
     pub fn show_background(mut self, show_background: bool) -> Self {
-        self.show_background = show_background;
+        self.frame.show_background = show_background;
+        self
+    }
+
+    /// The fill of the background of this header, when there is one
+    /// (see [`Self::show_background`]), instead of the one from the [`Visuals`].
+    #[inline]
+    pub fn frame_fill(mut self, fill: impl Into<Color32>) -> Self {
+        self.frame.fill = Some(fill.into());
+        self
+    }
+
+    /// The outline of the background of this header, like [`Self::frame_fill`].
+    #[inline]
+    pub fn frame_stroke(mut self, stroke: impl Into<Stroke>) -> Self {
+        self.frame.stroke = Some(stroke.into());
+        self
+    }
+
+    /// The corners of the background of this header, like [`Self::frame_fill`].
+    #[inline]
+    pub fn frame_rounding(mut self, rounding: impl Into<Rounding>) -> Self {
+        self.frame.rounding = Some(rounding.into());
+        self
+    }
+
+    /// Show a value after the title, e.g. a summary of the settings in the body.
+    ///
+    /// Use [`CollapsingGroup::align_values`] to line up the values of several headers.
+    #[inline]
+    pub fn value(mut self, value: impl Into<WidgetText>) -> Self {
+        self.value = Some(value.into());
+        self
+    }
+
+    /// Call this after the body has been shown, with the rect the body covers,
+    /// e.g. to draw a border or a resize handle along it.
+    ///
+    /// While animating, the rect only covers the visible part of the body.
+    /// Not called when the header is closed (or a [`Self::leaf`]).
+    ///
+    /// Like [`Self::icon`], the function can't borrow anything.
+    #[inline]
+    pub fn after_body(mut self, after_body: impl FnOnce(&mut Ui, Rect) + 'static) -> Self {
+        self.after_body = Some(Box::new(after_body));
+        self
+    }
+
+    /// Show this menu when right-clicking the header row.
+    ///
+    /// Right-clicking doesn't open or close the header.
+    /// Use [`CollapsingResponse::context_menu_opened`] to e.g. keep the row highlighted while the menu is open.
+    ///
+    /// Like [`Self::icon`], the function can't borrow anything.
+    /// To act on e.g. a "Rename" button, call [`Response::context_menu`] on
+    /// [`CollapsingResponse::header_response`] yourself instead.
+    #[inline]
+    pub fn context_menu(mut self, add_contents: impl FnOnce(&mut Ui) + 'static) -> Self {
+        self.context_menu = Some(Box::new(add_contents));
+        self
+    }
+
+    /// Open the header by itself when its body grows while it is closed,
+    /// e.g. for a log that should pop open when new lines arrive.
+    ///
+    /// While closed, the body is laid out invisibly each frame to measure it.
+    /// If the user collapses the header, it stays closed until the body grows again.
+    /// Not supported together with [`Self::chip_mode`].
+    #[inline]
+    pub fn auto_expand_on_growth(mut self, auto_expand_on_growth: bool) -> Self {
+        self.auto_expand_on_growth = auto_expand_on_growth;
+        self
+    }
+
+    /// The space between the bottom of the header and the top of the body,
+    /// instead of [`crate::style::Spacing::item_spacing`].
+    ///
+    /// Can be negative to let the body overlap the header, e.g. for a seamless card.
+    /// The gap grows with the body while opening, so nothing jumps.
+    /// Has no effect with [`Self::flyout`] or [`Self::chip_mode`].
+    #[inline]
+    pub fn header_body_gap(mut self, gap: f32) -> Self {
+        self.header_body_gap = Some(gap);
+        self
+    }
+
+    /// Keep calling `add_body` while the header is closed, in an invisible region that takes up no space.
+    ///
+    /// This keeps the widgets of the body alive, so they don't lose state that is only kept
+    /// while a widget is shown when the header is closed and opened again.
+    /// The hidden widgets are disabled, so they can't be interacted with or keep keyboard focus.
+    ///
+    /// This costs as much CPU as an open body (minus the painting), every frame,
+    /// so only use it for bodies that are cheap, or that you switch between often,
+    /// like the tabs of a [`CollapsingRadio`].
+    /// Not supported together with [`Self::chip_mode`].
+    #[inline]
+    pub fn keep_mounted(mut self, keep_mounted: bool) -> Self {
+        self.keep_mounted = keep_mounted;
+        self
+    }
+
+    /// Show your own contents in the header row, instead of the title text.
+    ///
+    /// Everything else works as usual: the arrow is shown and toggles the header,
+    /// and the frame, background, selection and id are as configured.
+    /// The function is called with a left-to-right [`Ui`] covering the part of the row
+    /// after the arrow, the openness, and the response of the arrow
+    /// (or the header, if there is no arrow).
+    ///
+    /// The size of the row is still computed from the text given to [`Self::new`],
+    /// which is also used as the [`Id`] source and for accessibility.
+    ///
+    /// Like [`Self::icon`], the function can't borrow anything,
+    /// so move (or clone) what it shows into it.
+    ///
+    /// ```
+    /// # egui::__run_test_ui(|ui| {
+    /// egui::CollapsingHeader::new("Downloads")
+    ///     .custom_header(|ui, _openness, _arrow_response| {
+    ///         ui.label("📁");
+    ///         ui.strong("Downloads");
+    ///         ui.weak("3 files");
+    ///     })
+    ///     .show(ui, |ui| ui.label("…"));
+    /// # });
+    /// ```
+    #[inline]
+    pub fn custom_header(
+        mut self,
+        add_contents: impl FnOnce(&mut Ui, f32, &Response) + 'static,
+    ) -> Self {
+        self.custom_header = Some(Box::new(add_contents));
         self
     }
 
@@ -469,213 +2338,5759 @@ impl CollapsingHeader {
     /// ```
     #[inline]
     pub fn icon(mut self, icon_fn: impl FnOnce(&mut Ui, f32, &Response) + 'static) -> Self {
-        self.icon = Some(Box::new(icon_fn));
+        self.icon.painter = Some(Box::new(icon_fn));
         self
     }
-}
 
-struct Prepared {
-    header_response: Response,
-    state: CollapsingState,
-    openness: f32,
-}
+    /// Use an image as the icon, e.g. a thumbnail, instead of the arrow.
+    ///
+    /// The image of the given size is fitted into the icon rect, keeping its aspect ratio.
+    /// Combine with [`Self::icon_scale_range`] to grow (or shrink) the image as the header opens.
+    /// A custom [`Self::icon`] takes precedence. See also [`paint_image_icon`].
+    #[inline]
+    pub fn image_icon(mut self, texture_id: TextureId, size: Vec2) -> Self {
+        self.icon.image = Some((texture_id, size));
+        self
+    }
 
-impl CollapsingHeader {
-    fn begin(self, ui: &mut Ui) -> Prepared {
-        assert!(
-            ui.layout().main_dir().is_vertical(),
-            "Horizontal collapsing is unimplemented"
-        );
-        let Self {
-            icon,
-            text,
-            default_open,
-            open,
-            id_source,
-            enabled: _,
-            selectable,
-            selected,
-            show_background,
-        } = self;
+    /// Show the header as a regular [`Button`] with the arrow after the title, like "Details ⏵",
+    /// instead of an arrow in the indentation followed by the title.
+    ///
+    /// Clicking the button toggles the body as usual.
+    /// Use [`Self::show_unindented`] if the body should not be indented.
+    #[inline]
+    pub fn button_style(mut self, button_style: bool) -> Self {
+        self.button_style = button_style;
+        self
+    }
 
-        // TODO(emilk): horizontal layout, with icon and text as labels. Insert background behind using Frame.
+    /// Should the open state and body height be stored in the [`Context`]? Default: `true`.
+    ///
+    /// Turn this off if you keep the open state in your own model:
+    /// the header is then a pure function of [`Self::open`] (or [`Self::default_open`])
+    /// and [`Self::estimated_body_height`], and nothing is stored for it.
+    /// Clicks don't toggle the header by themselves, so check [`CollapsingResponse::header_response`]
+    /// and update your model.
+    ///
+    /// The animation still works, since it is keyed on the id (or [`Self::anim_id`]),
+    /// or you can drive it yourself with [`Self::openness_override`].
+    #[inline]
+    pub fn manage_state(mut self, manage_state: bool) -> Self {
+        self.manage_state = manage_state;
+        self
+    }
 
-        let id = ui.make_persistent_id(id_source);
-        let button_padding = ui.spacing().button_padding;
+    /// Should the open state be persisted, e.g. with the `persistence` feature? Default: `true`.
+    ///
+    /// Turn this off for many short-lived headers, so they don't bloat the saved app state.
+    /// The state is then still stored, but only until the app restarts.
+    ///
+    /// See [`CollapsingState::set_persist`].
+    #[inline]
+    pub fn persist(mut self, persist: bool) -> Self {
+        self.persist = persist;
+        self
+    }
 
-        let available = ui.available_rect_before_wrap();
-        let text_pos = available.min + vec2(ui.spacing().indent, 0.0);
-        let wrap_width = available.right() - text_pos.x;
-        let galley = text.into_galley(
-            ui,
-            Some(TextWrapMode::Extend),
-            wrap_width,
-            TextStyle::Button,
-        );
-        let text_max_x = text_pos.x + galley.size().x;
+    /// The height of the body to use for the open/close animation until it has been measured.
+    ///
+    /// Without it, the first frame of opening uses a small placeholder height.
+    /// Useful with [`Self::manage_state`], where the measured height is not remembered,
+    /// or when the height of the body is known up front.
+    /// If the real height turns out to be different, it replaces the estimate once the body has been shown.
+    ///
+    /// See [`CollapsingState::set_estimated_open_height`].
+    #[inline]
+    pub fn estimated_body_height(mut self, height: f32) -> Self {
+        self.estimated_body_height = Some(height);
+        self
+    }
 
-        let mut desired_width = text_max_x + button_padding.x - available.left();
-        if ui.visuals().collapsing_header_frame {
-            desired_width = desired_width.max(available.width()); // fill full width
-        }
+    /// The height of a body with a known, fixed size, so that the first opening animates
+    /// towards it right away instead of a small placeholder height.
+    ///
+    /// The hint isn't trusted forever: if the body turns out to have another height,
+    /// the measured height replaces it on the first frame the body is fully open.
+    /// Same as [`Self::estimated_body_height`]; see [`CollapsingState::set_estimated_open_height`].
+    #[inline]
+    pub fn known_open_height(self, height: f32) -> Self {
+        self.estimated_body_height(height)
+    }
 
-        let mut desired_size = vec2(desired_width, galley.size().y + 2.0 * button_padding.y);
-        desired_size = desired_size.at_least(ui.spacing().interact_size);
-        let (_, rect) = ui.allocate_space(desired_size);
+    /// The width of the icon column of the header, and how much to indent the body,
+    /// instead of [`crate::style::Spacing::indent`], e.g. for a tighter tree.
+    ///
+    /// Only this header and its body indentation are affected, not the contents of the body.
+    #[inline]
+    pub fn indent(mut self, indent: f32) -> Self {
+        self.indent = Some(indent);
+        self
+    }
 
-        let mut header_response = ui.interact(rect, id, Sense::click());
-        let text_pos = pos2(
-            text_pos.x,
-            header_response.rect.center().y - galley.size().y / 2.0,
-        );
+    /// Indent the body by only a few points, with a guide line on the left,
+    /// instead of the full [`crate::style::Spacing::indent`].
+    ///
+    /// This keeps deep trees readable in narrow panels,
+    /// since each level of nesting only takes [`Self::FLAT_NESTING_INDENT`] of the width.
+    /// Has no effect with [`Self::show_unindented`].
+    #[inline]
+    pub fn flat_nesting(mut self, flat_nesting: bool) -> Self {
+        self.flat_nesting = flat_nesting;
+        self
+    }
 
-        let mut state = CollapsingState::load_with_default_open(ui.ctx(), id, default_open);
-        if let Some(open) = open {
-            if open != state.is_open() {
-                state.toggle(ui);
-                header_response.mark_changed();
-            }
-        } else if header_response.clicked() {
-            state.toggle(ui);
-            header_response.mark_changed();
-        }
+    /// Open and close instantly if the body is shorter than this. Default: 0 (always animate).
+    ///
+    /// See [`CollapsingState::set_min_animate_height`].
+    #[inline]
+    pub fn min_animate_height(mut self, min_animate_height: f32) -> Self {
+        self.animation.min_height = min_animate_height;
+        self
+    }
 
-        header_response.widget_info(|| {
-            WidgetInfo::labeled(WidgetType::CollapsingHeader, ui.is_enabled(), galley.text())
-        });
+    /// Show the body in a popup below the header, as wide as the header,
+    /// instead of in the layout, so that opening it doesn't move the widgets below.
+    ///
+    /// The popup animates open and closed as usual.
+    /// It closes when clicking outside of it (or on the header again), or when pressing escape.
+    /// The widgets in the popup take keyboard focus as usual; closing it doesn't move the focus.
+    #[inline]
+    pub fn flyout(mut self, flyout: bool) -> Self {
+        self.flyout = flyout;
+        self
+    }
 
-        let openness = state.openness(ui.ctx());
+    /// Show a pin at the end of the header row, which the user can click to pin the header open.
+    ///
+    /// A pinned header stays open when closing many headers at once,
+    /// e.g. with [`CollapsingState::collapse_all`]. The pinning is stored with the rest of the state.
+    /// See [`CollapsingState::set_pinned`].
+    #[inline]
+    pub fn pinnable(mut self, pinnable: bool) -> Self {
+        self.pinnable = pinnable;
+        self
+    }
 
-        if ui.is_rect_visible(rect) {
-            let visuals = ui.style().interact_selectable(&header_response, selected);
+    /// Dim the body and show a spinner over it, e.g. while its contents are being refreshed.
+    ///
+    /// The body is still shown (and keeps its height, so nothing moves),
+    /// but the overlay is on top of it, so the pointer can't reach the widgets of the body.
+    #[inline]
+    pub fn body_busy(mut self, body_busy: bool) -> Self {
+        self.body.busy = body_busy;
+        self
+    }
 
-            if ui.visuals().collapsing_header_frame || show_background {
-                ui.painter().add(epaint::RectShape::new(
-                    header_response.rect.expand(visuals.expansion),
-                    visuals.rounding,
-                    visuals.weak_bg_fill,
-                    visuals.bg_stroke,
-                ));
-            }
+    /// Show a small dot after the title, e.g. to mark a section with unsaved changes.
+    ///
+    /// The dot is painted in [`crate::Visuals::warn_fg_color`], in the space between the title
+    /// and the [`Self::value`], so it doesn't change the layout.
+    #[inline]
+    pub fn modified(mut self, modified: bool) -> Self {
+        self.modified = modified;
+        self
+    }
 
-            if selected || selectable && (header_response.hovered() || header_response.has_focus())
-            {
-                let rect = rect.expand(visuals.expansion);
+    /// Draw attention to the header with a pulsing outline until [`InputState::time`] reaches `until`,
+    /// e.g. to point out a newly added setting.
+    ///
+    /// Repaints continuously while the outline is shown.
+    /// After `until`, the header is shown as usual.
+    #[inline]
+    pub fn highlight_new(mut self, until: f64) -> Self {
+        self.highlight_new = Some(until);
+        self
+    }
 
-                ui.painter()
-                    .rect(rect, visuals.rounding, visuals.bg_fill, visuals.bg_stroke);
-            }
+    /// Apply the first of these events to the header each frame, and remove it from the queue,
+    /// e.g. to open and close sections one after the other in a guided tour.
+    ///
+    /// A repaint is requested while there are events left.
+    /// Make sure to keep the queue around between frames.
+    ///
+    /// ```
+    /// # egui::__run_test_ui(|ui| {
+    /// use egui::{collapsing_header::CollapsingEvent, mutex::Mutex};
+    /// use std::{collections::VecDeque, sync::Arc};
+    ///
+    /// let tour = Arc::new(Mutex::new(VecDeque::from([
+    ///     CollapsingEvent::Expand,
+    ///     CollapsingEvent::Collapse,
+    /// ])));
+    /// egui::CollapsingHeader::new("Settings")
+    ///     .display_queue(tour.clone())
+    ///     .show(ui, |ui| ui.label("…"));
+    /// # });
+    /// ```
+    #[inline]
+    pub fn display_queue(mut self, queue: Arc<Mutex<VecDeque<CollapsingEvent>>>) -> Self {
+        self.display_queue = Some(queue);
+        self
+    }
 
-            {
-                let (mut icon_rect, _) = ui.spacing().icon_rectangles(header_response.rect);
-                icon_rect.set_center(pos2(
-                    header_response.rect.left() + ui.spacing().indent / 2.0,
-                    header_response.rect.center().y,
-                ));
-                let icon_response = header_response.clone().with_new_rect(icon_rect);
-                if let Some(icon) = icon {
-                    icon(ui, openness, &icon_response);
-                } else {
-                    paint_default_icon(ui, openness, &icon_response);
-                }
-            }
+    /// How many seconds opening and closing takes, instead of [`Style::animation_time`].
+    ///
+    /// See [`CollapsingState::set_animation_time`].
+    #[inline]
+    pub fn animation_time(mut self, animation_time: f32) -> Self {
+        self.animation.time = Some(animation_time);
+        self
+    }
 
-            ui.painter().galley(text_pos, galley, visuals.text_color());
-        }
+    /// How to ease between closed and open. Default: [`AnimationStyle::Responsive`].
+    ///
+    /// With [`AnimationStyle::Overshoot`], the arrow turns a bit too far before settling,
+    /// while the body never grows past its full height.
+    #[inline]
+    pub fn animation(mut self, animation: AnimationStyle) -> Self {
+        self.animation.style = animation;
+        self
+    }
 
-        Prepared {
-            header_response,
-            state,
-            openness,
-        }
+    /// Fade out the bottom `fade_edge` points of the body while it animates,
+    /// for a softer edge than the sharp clipping. Default: 0 (sharp).
+    ///
+    /// See [`CollapsingState::set_fade_edge`].
+    #[inline]
+    pub fn fade_edge(mut self, fade_edge: f32) -> Self {
+        self.animation.fade_edge = fade_edge;
+        self
     }
 
+    /// Sense clicks on the empty space of the body, e.g. for "click to add an item".
+    ///
+    /// See [`CollapsingResponse::body_background_clicked`].
     #[inline]
-    pub fn show<R>(
-        self,
-        ui: &mut Ui,
-        add_body: impl FnOnce(&mut Ui) -> R,
-    ) -> CollapsingResponse<R> {
-        self.show_dyn(ui, Box::new(add_body), true)
+    pub fn sense_body_background(mut self, sense_body_background: bool) -> Self {
+        self.sense_body_background = sense_body_background;
+        self
     }
 
+    /// Put the header and the body together in one [`Frame`], like a card.
+    ///
+    /// The frame is sized to fit the header and the visible part of the body,
+    /// so it grows and shrinks smoothly as the body animates, with its border outside the clipped body.
+    /// Not supported together with [`Self::chip_mode`].
     #[inline]
-    pub fn show_unindented<R>(
-        self,
-        ui: &mut Ui,
-        add_body: impl FnOnce(&mut Ui) -> R,
-    ) -> CollapsingResponse<R> {
-        self.show_dyn(ui, Box::new(add_body), false)
+    pub fn card(mut self, frame: Frame) -> Self {
+        self.frame.card = Some(frame);
+        self
     }
 
-    fn show_dyn<'c, R>(
-        self,
-        ui: &mut Ui,
-        add_body: Box<dyn FnOnce(&mut Ui) -> R + 'c>,
+    /// If `true`, scroll the header and body into view when the header starts opening,
+    /// e.g. when opened with [`CollapsingState::set_open`] deep in a [`crate::ScrollArea`].
+    ///
+    /// The header is put at the top. See [`Self::scroll_to_body_on_open_align`] to change that.
+    /// Not supported together with [`Self::chip_mode`], or with [`Self::manage_state`] turned off.
+    /// Default: `false`.
+    #[inline]
+    pub fn scroll_to_body_on_open(mut self, scroll: bool) -> Self {
+        self.scroll_to_body_on_open = scroll.then_some(Align::TOP);
+        self
+    }
+
+    /// Like [`Self::scroll_to_body_on_open`], but with where to put the header and body in the scroll area.
+    #[inline]
+    pub fn scroll_to_body_on_open_align(mut self, align: Align) -> Self {
+        self.scroll_to_body_on_open = Some(align);
+        self
+    }
+
+    /// Where to put the title within the header row. Default: [`Align::Min`] (left).
+    ///
+    /// With [`Align::Center`] or [`Align::Max`] the header fills the available width,
+    /// and the [`Self::value`] (if any) moves to the end of the row.
+    /// The icon stays where it is; only the title moves.
+    #[inline]
+    pub fn text_align(mut self, text_align: Align) -> Self {
+        self.text_align = text_align;
+        self
+    }
+
+    /// Which of the built-in icons to use. Default: [`CollapsingIcon::Arrow`].
+    ///
+    /// Has no effect on headers with a custom [`Self::icon`], except that
+    /// [`CollapsingIcon::DotForLeaf`] still decides the indentation of leaves,
+    /// and [`CollapsingIcon::ChevronSwap`] still moves the icon to the end of the header.
+    #[inline]
+    pub fn icon_style(mut self, icon_style: CollapsingIcon) -> Self {
+        self.icon.style = icon_style;
+        self
+    }
+
+    /// Put the icon on the right edge of the header instead of before the title.
+    /// Default: [`HeaderIconSide::Left`].
+    ///
+    /// Also works with a custom [`Self::icon`].
+    #[inline]
+    pub fn icon_side(mut self, icon_side: HeaderIconSide) -> Self {
+        self.icon.side = icon_side;
+        self
+    }
+
+    /// Scale the default arrow icon with the openness, in addition to rotating it.
+    ///
+    /// The arrow is `range.start()` times the size of the icon rectangle when closed,
+    /// and `range.end()` times it when open.
+    /// The default is a constant `0.75`, so e.g. `0.5..=1.0` grows the arrow around its usual size.
+    ///
+    /// Has no effect if a custom [`Self::icon`] is used.
+    /// See also [`paint_default_icon_scaled`].
+    #[inline]
+    pub fn icon_scale_range(mut self, range: RangeInclusive<f32>) -> Self {
+        self.icon.scale_range = Some(range);
+        self
+    }
+
+    /// Size the default arrow icon relative to the icon rectangle. Default: `0.75`.
+    ///
+    /// Same as [`Self::icon_scale_range`] with a constant scale.
+    #[inline]
+    pub fn icon_scale(self, scale: f32) -> Self {
+        self.icon_scale_range(scale..=scale)
+    }
+
+    /// Always paint the default arrow icon in this color, instead of following hover and clicks.
+    ///
+    /// It still rotates as the header opens.
+    /// Has no effect if a custom [`Self::icon`] is used.
+    /// See also [`paint_default_icon_styled`].
+    #[inline]
+    pub fn icon_color(mut self, color: impl Into<Color32>) -> Self {
+        self.icon.color = Some(color.into());
+        self
+    }
+
+    /// Smoothly animate the height of the header row when it changes,
+    /// e.g. when the header text goes from one line to two. Default: `false`.
+    ///
+    /// This is independent of the animation of the body.
+    /// The icon and text stay vertically centered in the row while it animates.
+    #[inline]
+    pub fn animate_header_height(mut self, animate_header_height: bool) -> Self {
+        self.animation.header_height = animate_header_height;
+        self
+    }
+
+    /// Use this [`Id`] for the open/close animation, instead of the id of the stored state.
+    ///
+    /// Useful when the same header is shown in several places (sharing the same [`Self::id_source`]
+    /// and thus open state) but each place should animate independently.
+    ///
+    /// See [`CollapsingState::set_anim_id`].
+    #[inline]
+    pub fn anim_id(mut self, anim_id: Id) -> Self {
+        self.animation.id = Some(anim_id);
+        self
+    }
+
+    /// Let the user rename the header by double-clicking its title. Default: `false`.
+    ///
+    /// Double-clicking the title replaces it with a [`TextEdit`], starting out with the text given to [`Self::new`].
+    /// The edit is committed when pressing enter or when the edit loses focus,
+    /// and discarded when pressing escape.
+    /// A committed new title is returned in [`CollapsingResponse::renamed_title`],
+    /// for you to store and pass to [`Self::new`] from then on.
+    /// [`Self::show_editable`] does this for you.
+    ///
+    /// With an editable title only the arrow toggles the header, so that double-clicking
+    /// the title doesn't also open and close it.
+    ///
+    /// ```
+    /// # egui::__run_test_ui(|ui| {
+    /// # let mut name = String::from("Folder");
+    /// let response = egui::CollapsingHeader::new(name.as_str())
+    ///     .id_source("folder")
+    ///     .editable_title(true)
+    ///     .show(ui, |ui| ui.label("…"));
+    /// if let Some(new_name) = response.renamed_title {
+    ///     name = new_name;
+    /// }
+    /// # });
+    /// ```
+    #[inline]
+    pub fn editable_title(mut self, editable_title: bool) -> Self {
+        self.editable_title = editable_title;
+        self
+    }
+
+    /// Make the header row as short as its text, for dense views. Default: `false`.
+    ///
+    /// This removes the [`crate::style::Spacing::button_padding`] around the text,
+    /// and only requires the row to be tall enough to fit the icon,
+    /// instead of [`crate::style::Spacing::interact_size`].
+    #[inline]
+    pub fn compact(mut self, compact: bool) -> Self {
+        self.compact = compact;
+        self
+    }
+
+    /// Show this as a leaf of a tree: a clickable label without an arrow or body. Default: `false`.
+    ///
+    /// A leaf doesn't reserve the indentation for the arrow,
+    /// so leaves in the body of a header line up with the title of that header.
+    ///
+    /// The body passed to [`Self::show`] is never shown,
+    /// but [`CollapsingResponse::header_response`] still reports clicks, e.g. for selection.
+    #[inline]
+    pub fn leaf(mut self, leaf: bool) -> Self {
+        self.leaf = leaf;
+        self
+    }
+
+    /// Show this as a static section label among real headers: no arrow and no body. Default: `false`.
+    ///
+    /// Unlike [`Self::leaf`], the text keeps the indentation of the arrow,
+    /// so it lines up with the titles of the other headers,
+    /// and the header has the same size and frame as a collapsed header.
+    ///
+    /// The body passed to [`Self::show`] is never shown.
+    #[inline]
+    pub fn non_collapsible(mut self, non_collapsible: bool) -> Self {
+        self.non_collapsible = non_collapsible;
+        self
+    }
+
+    /// Send `(id, is_open)` on this channel whenever the header is opened or closed.
+    ///
+    /// This only sends when the open state actually changes, not every frame.
+    /// The message is sent synchronously while the header is being shown.
+    #[inline]
+    pub fn notify(mut self, sender: std::sync::mpsc::Sender<(Id, bool)>) -> Self {
+        self.notify = Some(sender);
+        self
+    }
+
+    /// Limit the height of the body. Taller bodies are shown in a [`ScrollArea`].
+    ///
+    /// Default: `None` (no limit).
+    #[inline]
+    pub fn max_body_height(mut self, max_body_height: f32) -> Self {
+        self.body.max_height = Some(max_body_height);
+        self
+    }
+
+    /// When the body is taller than [`Self::max_body_height`], show a clickable
+    /// "… N pt more" footer below it, where N is the height of the hidden part of the body in points.
+    ///
+    /// Clicking the footer removes the height limit for the rest of the session.
+    /// Use [`Self::height_capped_footer`] to change the text of the footer.
+    /// Default: `false`.
+    #[inline]
+    pub fn show_more_footer(mut self, show_more_footer: bool) -> Self {
+        self.body.show_more_footer = show_more_footer.then(|| {
+            Arc::new(|hidden_height: f32| format!("… {hidden_height:.0} pt more").into())
+                as FooterText
+        });
+        self
+    }
+
+    /// Like [`Self::show_more_footer`], but with the text of the footer
+    /// made from the height of the hidden part of the body, in points.
+    ///
+    /// ```
+    /// # egui::__run_test_ui(|ui| {
+    /// let rows = 1000;
+    /// let row_height = 18.0;
+    /// egui::CollapsingHeader::new("Log")
+    ///     .max_body_height(200.0)
+    ///     .height_capped_footer(move |hidden_height| {
+    ///         format!("Show {:.0} more rows", hidden_height / row_height).into()
+    ///     })
+    ///     .show(ui, |ui| {
+    ///         for i in 0..rows {
+    ///             ui.label(format!("Line {i}"));
+    ///         }
+    ///     });
+    /// # });
+    /// ```
+    #[inline]
+    pub fn height_capped_footer(mut self, text: impl Fn(f32) -> WidgetText + 'static) -> Self {
+        self.body.show_more_footer = Some(Arc::new(text));
+        self
+    }
+
+    /// If `true`, alt-clicking the header opens or closes it together with all headers inside it.
+    ///
+    /// A plain click still only toggles this header. Default: `false`.
+    ///
+    /// Only headers that have been shown before are affected, see [`CollapsingState::children_of`].
+    /// Use [`Self::recursive_click_modifiers`] to use some other modifier than alt.
+    #[inline]
+    pub fn alt_click_recursive(mut self, alt_click_recursive: bool) -> Self {
+        self.recursive_click_modifiers = alt_click_recursive.then_some(Modifiers::ALT);
+        self
+    }
+
+    /// Clicking the header while holding down these modifiers opens or closes it
+    /// together with all headers inside it.
+    ///
+    /// See [`Self::alt_click_recursive`].
+    #[inline]
+    pub fn recursive_click_modifiers(mut self, modifiers: Modifiers) -> Self {
+        self.recursive_click_modifiers = Some(modifiers);
+        self
+    }
+
+    /// Use this openness (0 = closed, 1 = open) for the icon and body,
+    /// instead of animating it, e.g. to drive it from your own animation system.
+    ///
+    /// The open state is still changed and stored as usual.
+    /// `None` (the default) uses the built-in animation.
+    ///
+    /// See [`CollapsingState::set_openness_override`].
+    #[inline]
+    pub fn openness_override(mut self, openness: Option<f32>) -> Self {
+        self.animation.openness_override = openness;
+        self
+    }
+
+    /// Show the header as closed while there is less than `min_height` of space below it,
+    /// e.g. for low-priority sections of a crowded panel.
+    ///
+    /// This doesn't change the stored open state:
+    /// once there is enough space again, the header is shown open again if it was open.
+    /// Clicking the header still toggles the stored state as usual.
+    /// An [`Self::openness_override`] takes precedence.
+    #[inline]
+    pub fn collapse_if_space_below(mut self, min_height: f32) -> Self {
+        self.collapse_if_space_below = Some(min_height);
+        self
+    }
+
+    /// Wait with showing the body until `ready` is `true`, e.g. while its contents are loaded
+    /// in the background.
+    ///
+    /// While the header is open but not ready, the arrow shows it as open
+    /// and a spinner is shown next to the title, but the body stays closed.
+    /// Once `ready`, the body animates open as usual, so there is no flash of an empty body.
+    ///
+    /// See also [`CollapsingState::set_pending`].
+    #[inline]
+    pub fn open_when_ready(mut self, ready: bool) -> Self {
+        self.open_when_ready = Some(ready);
+        self
+    }
+
+    /// Round the bottom corners of the body while it is animating,
+    /// to match the rounding of the header frame.
+    ///
+    /// Only has an effect when the header frame is drawn,
+    /// i.e. with [`Visuals::collapsing_header_frame`] or [`Self::show_background`].
+    ///
+    /// egui can only clip to rectangles, so the corners are instead masked
+    /// by painting them with `background`, which should be the color behind the header
+    /// (e.g. [`Visuals::panel_fill`]). On anything but a solid background
+    /// you are better off with the default straight clip.
+    #[inline]
+    pub fn rounded_body_clip(mut self, background: Color32) -> Self {
+        self.frame.rounded_body_clip = Some(background);
+        self
+    }
+
+    /// When opened, only show the first `peek_height` points of the body,
+    /// with a "Show more" link that reveals the rest (see [`Self::peek_more_text`]).
+    ///
+    /// Opening then animates in two stages: from closed to the peek,
+    /// and (when clicking "Show more") from the peek to the full body.
+    /// Closing the header goes back to peeking, see [`CollapsingState::is_fully_open`].
+    ///
+    /// While peeking, the bottom of the visible part fades out with a gradient
+    /// from transparent to [`Visuals::panel_fill`], to show that there is more.
+    ///
+    /// Bodies no taller than `peek_height` are always shown in full.
+    #[inline]
+    pub fn peek_height(mut self, peek_height: f32) -> Self {
+        self.body.peek_height = Some(peek_height);
+        self
+    }
+
+    /// The text of the link that reveals the rest of the body, see [`Self::peek_height`].
+    ///
+    /// Default: "Show more".
+    #[inline]
+    pub fn peek_more_text(mut self, text: impl Into<WidgetText>) -> Self {
+        self.body.peek_more_text = text.into();
+        self
+    }
+
+    /// How the body appears while animating. Default: [`RevealStyle::Clip`].
+    ///
+    /// In both cases only the visible part of the body takes up space in the layout
+    /// (unless [`Self::clamp_body_rect`] is turned off),
+    /// so the widgets below the body move the same way.
+    #[inline]
+    pub fn reveal_style(mut self, reveal_style: RevealStyle) -> Self {
+        self.animation.reveal_style = reveal_style;
+        self
+    }
+
+    /// Paint diagnostics of the body animation next to the body:
+    /// the measured and visible heights, the openness, and the clip rect.
+    ///
+    /// This is a development aid, e.g. for reporting animation glitches, and is off by default.
+    /// See [`CollapsingState::set_debug`].
+    #[inline]
+    pub fn debug(mut self, debug: bool) -> Self {
+        self.debug = debug;
+        self
+    }
+
+    /// Use the header as a drop target for drag-and-drop, e.g. to move items into a folder.
+    /// Default: `false`.
+    ///
+    /// While something is dragged over the header, [`CollapsingResponse::drop_hovered`] is set,
+    /// so you can highlight the header, and use [`Response::dnd_release_payload`] on
+    /// [`CollapsingResponse::header_response`] to perform the move.
+    ///
+    /// A closed header that is hovered during a drag opens after a short moment,
+    /// so that you can drop into its body.
+    #[inline]
+    pub fn drop_target(mut self, drop_target: bool) -> Self {
+        self.drop_target = drop_target;
+        self
+    }
+
+    /// Paint this color behind the body while the header or body is hovered.
+    ///
+    /// Default: `None` (no background).
+    #[inline]
+    pub fn body_hover_background(mut self, color: Color32) -> Self {
+        self.frame.body_hover_background = Some(color);
+        self
+    }
+
+    /// How the header row responds to the mouse. Default: [`Sense::click`].
+    ///
+    /// Clicking the row toggles the header if the sense includes clicks.
+    /// The arrow is sensed separately, see [`Self::arrow_sense`].
+    #[inline]
+    pub fn header_sense(mut self, sense: Sense) -> Self {
+        self.header_sense = sense;
+        self
+    }
+
+    /// How the arrow at the start of the header responds to the mouse. Default: [`Sense::click`].
+    ///
+    /// With e.g. [`Sense::hover`] you can show a preview when hovering the arrow,
+    /// while clicks on it go to the header row.
+    /// The response is available in [`CollapsingResponse::toggle_response`].
+    ///
+    /// The arrow never takes keyboard focus; the header does, see [`CollapsingResponse::header_has_focus`].
+    #[inline]
+    pub fn arrow_sense(mut self, sense: Sense) -> Self {
+        self.arrow_sense = sense;
+        self
+    }
+
+    /// Slide the body in from the left while opening (and back while closing),
+    /// by animating its indentation together with its height. Default: `false`.
+    ///
+    /// The body is laid out with its final width the whole time, so text doesn't reflow.
+    #[inline]
+    pub fn animate_indent(mut self, animate_indent: bool) -> Self {
+        self.animation.indent = animate_indent;
+        self
+    }
+
+    /// While animating, should the body pretend to only be as tall as its visible part?
+    /// Default: `true`.
+    ///
+    /// See [`CollapsingState::set_clamp_body_rect`] for when to turn this off.
+    #[inline]
+    pub fn clamp_body_rect(mut self, clamp_body_rect: bool) -> Self {
+        self.clamp_body_rect = clamp_body_rect;
+        self
+    }
+
+    /// Show the header as a small chip that can be placed in a horizontal layout,
+    /// e.g. [`Ui::horizontal_wrapped`].
+    ///
+    /// The header never fills the full width, and the body is shown
+    /// where [`ChipBody`] says instead of inline.
+    #[inline]
+    pub fn chip_mode(mut self, chip_body: ChipBody) -> Self {
+        self.chip_mode = Some(chip_body);
+        self
+    }
+
+    /// If you set this to `false`, the widgets in the body will be disabled,
+    /// but the header can still be used to open and close it. Default: `true`.
+    ///
+    /// Useful for sections that can be browsed but not edited, e.g. in a read-only mode.
+    /// Scrolling a body capped with [`Self::max_body_height`] still works.
+    ///
+    /// See also [`Self::enabled`], which disables the header as well.
+    #[inline]
+    pub fn body_enabled(mut self, body_enabled: bool) -> Self {
+        self.body.enabled = body_enabled;
+        self
+    }
+
+    /// Reuse the text layout of the header from the previous frame while it is fully collapsed.
+    /// Default: `false`.
+    ///
+    /// This only caches the text layout, which makes thousands of collapsed headers
+    /// with long titles somewhat cheaper to show.
+    /// Everything else about the header is done as usual every frame:
+    /// the row is allocated, interacted with, and painted.
+    ///
+    /// The cache is keyed by the [`text::LayoutJob`] of the title together with the pixels per point.
+    /// The job holds the text, the font and color of every section
+    /// (as resolved from the [`RichText`] and the [`Style`], e.g. [`Style::override_text_style`]),
+    /// and the wrapping for the available width.
+    /// So any change to these lays out the text again.
+    /// The hover and click colors of the header are applied when painting, and are not part of the key.
+    #[inline]
+    pub fn cache_collapsed_galley(mut self, cache_collapsed_galley: bool) -> Self {
+        self.cache_collapsed_galley = cache_collapsed_galley;
+        self
+    }
+
+    /// Paint the shapes of the body from an earlier frame instead of calling `add_body`,
+    /// while the header stays fully open. Default: `false`.
+    ///
+    /// This is for big static bodies, like a long formatted document,
+    /// that are expensive to lay out every frame.
+    ///
+    /// The shapes the body paints to the layer of the header are recorded the first frame
+    /// the header is fully open and the whole body is inside the clip rectangle
+    /// (e.g. not scrolled partly out of view), and are then replayed (moved along with the header) until:
+    /// * the header starts closing,
+    /// * the available width, the pixels per point or the [`Style`] changes, or
+    /// * [`Self::invalidate_body`] is called.
+    ///
+    /// Call [`Self::invalidate_body`] whenever the content of the body changes.
+    /// While replaying, `add_body` isn't called, so [`CollapsingResponse::body_returned`] is `None`.
+    ///
+    /// Only use this for non-interactive bodies: no widgets are created while replaying,
+    /// so there is nothing to hover, click or focus.
+    /// Anything the body shows in other layers (e.g. tooltips or popups) isn't replayed.
+    /// Has no effect with [`Self::chip_mode`].
+    #[inline]
+    pub fn cache_body(mut self, cache_body: bool) -> Self {
+        self.cache_body = cache_body;
+        self
+    }
+
+    /// Don't call `add_body` while the header is animating, for bodies that are expensive to build.
+    /// Default: `false`.
+    ///
+    /// While opening or closing, the space the body took up last time is left empty instead,
+    /// so the header below still moves smoothly.
+    /// `add_body` is called once the header is fully open,
+    /// and also on the first frame it is opened, to measure the body.
+    /// While skipped, [`CollapsingResponse::body_returned`] is `None`.
+    #[inline]
+    pub fn lazy(mut self, lazy: bool) -> Self {
+        self.lazy = lazy;
+        self
+    }
+
+    /// Forget the body shapes recorded by [`Self::cache_body`] for the header with this id,
+    /// so that `add_body` is called (and recorded) again the next frame.
+    ///
+    /// The id is the one of [`CollapsingResponse::header_response`].
+    pub fn invalidate_body(ctx: &Context, id: Id) {
+        ctx.data_mut(|d| d.remove::<BodyCache>(id.with("body_cache")));
+    }
+
+    /// Store app-specific data (e.g. a user note) for this header,
+    /// persisted together with its open state.
+    ///
+    /// Read it back with [`CollapsingState::user_data`] using the id of the header.
+    #[inline]
+    pub fn with_user_data<T: SerializableAny>(mut self, user_data: T) -> Self {
+        self.user_data = Some(Box::new(move |ctx, id| {
+            CollapsingState::set_user_data(ctx, id, user_data);
+        }));
+        self
+    }
+}
+
+struct Prepared {
+    header_response: Response,
+    arrow_response: Option<Response>,
+    state: CollapsingState,
+    openness: f32,
+    prev_openness: f32,
+    drop_hovered: bool,
+    applied_event: Option<(CollapsingEvent, bool)>,
+    renamed_title: Option<String>,
+}
+
+/// How long to hover a closed [`CollapsingHeader::drop_target`] during a drag before it opens.
+const SPRING_LOADED_DELAY: f64 = 0.7;
+
+impl CollapsingHeader {
+    /// The [`Id`] this header will have when shown in the given [`Ui`].
+    ///
+    /// Use it to e.g. pre-seed or query the [`CollapsingState`] of the header,
+    /// or to find it in [`CollapsingState::children_of`].
+    ///
+    /// Must be called with the same [`Ui`] as the header will be shown in
+    /// (not e.g. one created with [`Ui::horizontal`] or [`Ui::push_id`] around it),
+    /// since the id depends on the id of the [`Ui`].
+    pub fn resolve_id(&self, ui: &Ui) -> Id {
+        // The header is shown in a child `Ui` (of `ui.vertical` or `ui.child_ui`):
+        ui.id().with("child").with(self.id_source)
+    }
+
+    /// The size the header row wants, without allocating any space or painting anything.
+    ///
+    /// This runs the same computation as [`Self::show`] does before allocating the header,
+    /// so it can be used to e.g. find the widest of many headers and size a column after it.
+    ///
+    /// Must be called with the same [`Ui`] (or one with the same available space and style)
+    /// as the header will be shown in.
+    /// Ignores [`Self::animate_header_height`] and [`CollapsingGroup::align_values`].
+    pub fn measure(&self, ui: &Ui) -> Vec2 {
+        let galley = self.header_galley(ui, self.text.clone());
+        self.header_size(ui, &galley)
+    }
+
+    /// The height of the header row, without the body, as [`Self::measure`]d.
+    ///
+    /// This is the height of the title plus the button padding,
+    /// but at least [`crate::style::Spacing::interact_size`] (or the icon width when [`Self::compact`]).
+    /// Use it to e.g. virtualize a long list of closed headers,
+    /// only showing the ones in view and allocating empty space for the rest.
+    pub fn header_height(&self, ui: &Ui) -> f32 {
+        self.measure(ui).y
+    }
+
+    /// How far from the left of the header the text starts.
+    fn text_indent(&self, ui: &Ui) -> f32 {
+        if self.button_style {
+            ui.spacing().button_padding.x
+        } else if self.leaf && self.icon.style != CollapsingIcon::DotForLeaf {
+            0.0
+        } else if self.icon.style == CollapsingIcon::ChevronSwap
+            || self.icon.side == HeaderIconSide::Right && !self.leaf
+        {
+            ui.spacing().button_padding.x
+        } else {
+            self.indent.unwrap_or(ui.spacing().indent)
+        }
+    }
+
+    /// Is the icon at the end of the header rather than before the title?
+    fn trailing_icon(&self) -> bool {
+        self.button_style
+            || !self.leaf
+                && (self.icon.style == CollapsingIcon::ChevronSwap
+                    || self.icon.side == HeaderIconSide::Right)
+    }
+
+    fn header_galley(&self, ui: &Ui, text: WidgetText) -> Arc<Galley> {
+        let available = ui.available_rect_before_wrap();
+        let wrap_width = available.width() - self.text_indent(ui);
+        text.into_galley(
+            ui,
+            Some(TextWrapMode::Extend),
+            wrap_width,
+            TextStyle::Button,
+        )
+    }
+
+    /// Like [`Self::header_galley`], but reuses the galley stored by [`Self::begin`]
+    /// while the header is collapsed, if it was laid out with the same key.
+    fn cached_header_galley(&self, ui: &Ui, id: Id, text: WidgetText) -> (u64, Arc<Galley>) {
+        let ppp = ui.ctx().pixels_per_point().to_bits();
+        if let WidgetText::Galley(galley) = &text {
+            // Already laid out, nothing to cache:
+            return (crate::util::hash((&*galley.job, ppp)), galley.clone());
+        }
+        let available = ui.available_rect_before_wrap();
+        let wrap_width = available.width() - self.text_indent(ui);
+        // The same job as `Self::header_galley` lays out, so it already holds the text,
+        // the fonts and colors resolved from the style, and the wrapping:
+        let mut job = text.into_layout_job(
+            ui.style(),
+            TextStyle::Button.into(),
+            ui.layout().vertical_align(),
+        );
+        job.wrap = text::TextWrapping::from_wrap_mode_and_width(TextWrapMode::Extend, wrap_width);
+        // Text is laid out for the pixels per point, so relayout when zooming or moving to another screen:
+        let key = crate::util::hash((&job, ppp));
+        let cached = ui.data(|d| d.get_temp::<(u64, Arc<Galley>)>(id.with("collapsed_galley")));
+        match cached {
+            Some((cached_key, galley)) if cached_key == key => (key, galley),
+            _ => (key, ui.fonts(|f| f.layout_job(job))),
+        }
+    }
+
+    fn value_galley(ui: &Ui, value: WidgetText) -> Arc<Galley> {
+        value.into_galley(
+            ui,
+            Some(TextWrapMode::Extend),
+            f32::INFINITY,
+            TextStyle::Button,
+        )
+    }
+
+    fn header_size(&self, ui: &Ui, galley: &Galley) -> Vec2 {
+        let available = ui.available_rect_before_wrap();
+        let mut min_size = ui.spacing().interact_size;
+        let button_padding = if self.compact {
+            min_size.y = ui.spacing().icon_width;
+            Vec2::ZERO
+        } else {
+            ui.spacing().button_padding
+        };
+
+        let mut desired_width = self.text_indent(ui) + galley.size().x + button_padding.x;
+        if let Some(value) = &self.value {
+            desired_width +=
+                ui.spacing().item_spacing.x + Self::value_galley(ui, value.clone()).size().x;
+        }
+        if self.trailing_icon() {
+            // The icon goes after the title:
+            desired_width += ui.spacing().item_spacing.x + ui.spacing().icon_width;
+        }
+        if self.pinnable {
+            desired_width += ui.spacing().item_spacing.x + ui.spacing().icon_width;
+        }
+        let fill_width = ui.visuals().collapsing_header_frame || self.text_align != Align::Min;
+        let horizontal = ui.layout().main_dir().is_horizontal();
+        if fill_width && self.chip_mode.is_none() && !self.button_style && !horizontal {
+            desired_width = desired_width.max(available.width()); // fill full width
+        }
+
+        let desired_size = vec2(desired_width, galley.size().y + 2.0 * button_padding.y);
+        desired_size.at_least(min_size)
+    }
+
+    fn begin(mut self, ui: &mut Ui, id: Id) -> Prepared {
+        let trailing_icon = self.trailing_icon();
+        // The body goes to the right of the header:
+        let horizontal = ui.layout().main_dir().is_horizontal();
+        let text = std::mem::take(&mut self.text);
+        let (galley_key, galley) = if self.cache_collapsed_galley {
+            let (key, galley) = self.cached_header_galley(ui, id, text);
+            (Some(key), galley)
+        } else {
+            (None, self.header_galley(ui, text))
+        };
+        let mut desired_size = self.header_size(ui, &galley);
+        let text_indent = self.text_indent(ui);
+        let value_galley = self.value.take().map(|value| Self::value_galley(ui, value));
+
+        let Self {
+            icon:
+                IconOptions {
+                    painter: icon,
+                    style: icon_style,
+                    side: _,
+                    scale_range: icon_scale_range,
+                    color: icon_color,
+                    image: image_icon,
+                },
+            text: _,
+            default_open,
+            open,
+            open_once,
+            id_source: _,
+            enabled: _,
+            disabled_reason,
+            disabled_alpha: _,
+            selectable,
+            selected,
+            frame:
+                FrameOptions {
+                    show_background,
+                    fill: frame_fill,
+                    stroke: frame_stroke,
+                    rounding: frame_rounding,
+                    card: _,
+                    rounded_body_clip: _,
+                    body_hover_background: _,
+                },
+            animation:
+                AnimationOptions {
+                    header_height: animate_header_height,
+                    id: anim_id,
+                    openness_override,
+                    indent: _,
+                    reveal_style,
+                    min_height: min_animate_height,
+                    fade_edge,
+                    style: animation,
+                    time: animation_time,
+                },
+            editable_title,
+            compact,
+            leaf,
+            notify,
+            body: _,
+            recursive_click_modifiers,
+            collapse_if_space_below,
+            header_sense,
+            arrow_sense,
+            clamp_body_rect,
+            chip_mode: _,
+            user_data,
+            cache_collapsed_galley: _,
+            cache_body: _,
+            lazy: _,
+            open_when_ready,
+            non_collapsible,
+            click_toggles,
+            drop_target,
+            debug,
+            custom_header,
+            value: _,
+            text_align,
+            button_style,
+            scroll_to_body_on_open: _,
+            manage_state,
+            persist,
+            estimated_body_height,
+            flat_nesting: _,
+            indent: _,
+            sense_body_background: _,
+            display_queue,
+            modified,
+            highlight_new,
+            pinnable,
+            flyout: _,
+            after_body: _,
+            context_menu,
+            auto_expand_on_growth: _,
+            keep_mounted: _,
+            header_body_gap: _,
+        } = self;
+        let leaf_dot = leaf && icon_style == CollapsingIcon::DotForLeaf;
+        // Neither has an arrow nor a body:
+        let leaf = leaf || non_collapsible;
+
+        let available = ui.available_rect_before_wrap();
+        let text_pos = available.min + vec2(text_indent, 0.0);
+        // Mirror the header in a right-aligned layout, e.g. for right-to-left languages:
+        let rtl = !horizontal && !trailing_icon && ui.layout().horizontal_align() == Align::Max;
+        let trailing_padding = if compact {
+            0.0
+        } else {
+            ui.spacing().button_padding.x
+        };
+
+        // Where the value goes, relative to the left of the header:
+        let min_value_offset = text_indent + galley.size().x + ui.spacing().item_spacing.x;
+        let value_offset = value_galley.as_ref().map(|value_galley| {
+            let offset = if text_align == Align::Min {
+                HeaderRegistry::value_column(ui.ctx()).map_or(min_value_offset, |column| {
+                    (column - available.left()).at_least(min_value_offset)
+                })
+            } else {
+                // At the end of the row:
+                (available.width() - trailing_padding - value_galley.size().x)
+                    .at_least(min_value_offset)
+            };
+            desired_size.x = desired_size.x.max(offset + value_galley.size().x);
+            offset
+        });
+
+        if animate_header_height {
+            desired_size.y = ui.ctx().animate_value_with_time(
+                id.with("header_height"),
+                desired_size.y,
+                ui.style().animation_time,
+            );
+        }
+        let (_, rect) = ui.allocate_space(desired_size);
+
+        let mut header_response = ui.interact(rect, id, header_sense);
+        // The x coordinate of the center of a trailing icon:
+        let trailing_icon_x = {
+            let pin_width = if pinnable && !leaf {
+                ui.spacing().icon_width + ui.spacing().item_spacing.x
+            } else {
+                0.0
+            };
+            rect.right() - trailing_padding - pin_width - ui.spacing().icon_width / 2.0
+        };
+        let arrow_response = (!leaf && !button_style).then(|| {
+            let arrow_rect = if trailing_icon {
+                Rect::from_center_size(
+                    pos2(trailing_icon_x, rect.center().y),
+                    vec2(ui.spacing().icon_width, rect.height()),
+                )
+            } else if rtl {
+                Rect::from_min_max(pos2(rect.right() - text_indent, rect.top()), rect.max)
+            } else {
+                Rect::from_min_size(rect.min, vec2(text_indent, rect.height()))
+            };
+            // The header already toggles with the keyboard, so don't make the arrow an extra tab stop:
+            let arrow_sense = Sense {
+                focusable: false,
+                ..arrow_sense
+            };
+            ui.interact(arrow_rect, id.with("arrow"), arrow_sense)
+        });
+        // After the header, so that it is on top and gets the clicks:
+        let pin_response = (pinnable && !leaf).then(|| {
+            let center = pos2(
+                rect.right() - trailing_padding - ui.spacing().icon_width / 2.0,
+                rect.center().y,
+            );
+            let pin_rect = Rect::from_center_size(center, Vec2::splat(ui.spacing().icon_width));
+            ui.interact(pin_rect, id.with("pin"), Sense::click())
+        });
+        let value_x = value_galley
+            .is_some()
+            .then(|| rect.left() + min_value_offset);
+        HeaderRegistry::register(ui.ctx(), id, galley.text(), rect, value_x);
+        let text_x = if rtl {
+            rect.right() - text_indent - galley.size().x
+        } else if text_align == Align::Min {
+            text_pos.x
+        } else {
+            let end = match value_offset {
+                Some(value_offset) => rect.left() + value_offset - ui.spacing().item_spacing.x,
+                None => rect.right() - trailing_padding,
+            };
+            let range = Rangef::new(text_pos.x, end.at_least(text_pos.x));
+            let x = text_align
+                .align_size_within_range(galley.size().x, range)
+                .min;
+            x.at_least(text_pos.x)
+        };
+        let text_pos = pos2(
+            text_x,
+            header_response.rect.center().y - galley.size().y / 2.0,
+        );
+
+        let title_edit_id = id.with("title_edit");
+        let mut title_buffer = if editable_title {
+            ui.data(|d| d.get_temp::<String>(title_edit_id))
+        } else {
+            None
+        };
+        let clicked_title = editable_title
+            && header_response.interact_pointer_pos().map_or(false, |pos| {
+                if rtl {
+                    pos.x <= text_pos.x + galley.size().x
+                } else {
+                    text_pos.x <= pos.x
+                }
+            });
+        if title_buffer.is_none() && clicked_title && header_response.double_clicked() {
+            title_buffer = Some(galley.text().to_owned());
+            ui.memory_mut(|mem| mem.request_focus(title_edit_id));
+        }
+
+        let mut state = if manage_state {
+            CollapsingState::load_with_default_open(ui.ctx(), id, default_open)
+        } else {
+            CollapsingState::new(id, open.unwrap_or(default_open))
+        };
+        state.set_manage_state(manage_state);
+        state.set_persist(persist);
+        if let Some(height) = estimated_body_height {
+            state.set_estimated_open_height(height);
+        }
+        if let Some(anim_id) = anim_id {
+            state.set_anim_id(anim_id);
+        }
+        // Too crowded for the body this frame; the open state is kept for when there is room again:
+        let no_room = collapse_if_space_below.map_or(false, |min| ui.available_height() < min);
+        state.set_openness_override(openness_override.or(no_room.then_some(0.0)));
+        state.set_clamp_body_rect(clamp_body_rect);
+        state.set_reveal_style(reveal_style);
+        state.set_debug(debug);
+        state.set_min_animate_height(min_animate_height);
+        state.set_fade_edge(fade_edge);
+        state.set_animation(animation);
+        state.set_animation_time(animation_time);
+        state.set_horizontal(horizontal);
+        if let Some(store_user_data) = user_data {
+            store_user_data(ui.ctx(), id);
+        }
+        if let Some(pin_response) = &pin_response {
+            if pin_response.clicked() {
+                state.set_pinned(!state.is_pinned());
+                state.store(ui.ctx());
+            }
+        }
+        let was_open = state.is_open();
+        let open = open.or_else(|| {
+            // Kept in the state, which is only written when this changes:
+            let prev = std::mem::replace(&mut state.state.open_once, open_once);
+            open_once.filter(|&open| prev != Some(open))
+        });
+        if let Some(open) = open {
+            if open != state.is_open() {
+                state.toggle(ui);
+                header_response.mark_changed();
+            }
+        } else if !leaf
+            && click_toggles
+            && (header_response.clicked() && !clicked_title
+                || arrow_response.as_ref().map_or(false, |r| r.clicked()))
+        {
+            state.toggle(ui);
+            header_response.mark_changed();
+            if let Some(modifiers) = recursive_click_modifiers {
+                if ui.input(|i| i.modifiers.matches_logically(modifiers)) {
+                    CollapsingState::set_subtree_open(ui.ctx(), id, state.is_open());
+                }
+            }
+        } else if !leaf && click_toggles && header_response.has_focus() {
+            // Right opens and Left closes, like in a tree view.
+            ui.memory_mut(|mem| {
+                mem.set_focus_lock_filter(
+                    id,
+                    EventFilter {
+                        horizontal_arrows: true,
+                        ..Default::default()
+                    },
+                );
+            });
+            let (open, close) = ui.input(|i| {
+                (
+                    i.key_pressed(Key::ArrowRight),
+                    i.key_pressed(Key::ArrowLeft),
+                )
+            });
+            if open && !state.is_open() || close && state.is_open() {
+                state.toggle(ui);
+                header_response.mark_changed();
+            }
+        }
+
+        let mut applied_event = None;
+        if let Some(queue) = display_queue {
+            let mut queue = queue.lock();
+            applied_event = queue.pop_front();
+            let open = applied_event.map(|event| match event {
+                CollapsingEvent::Expand => true,
+                CollapsingEvent::Collapse => false,
+                CollapsingEvent::Toggle => !state.is_open(),
+            });
+            if open.map_or(false, |open| !leaf && open != state.is_open()) {
+                state.toggle(ui);
+                header_response.mark_changed();
+            }
+            if !queue.is_empty() {
+                ui.ctx().request_repaint();
+            }
+        }
+
+        let drop_hovered = drop_target
+            && header_response.contains_pointer()
+            && (DragAndDrop::has_any_payload(ui.ctx()) || ui.ctx().dragged_id().is_some());
+        let drop_hover_id = id.with("drop_hovered_since");
+        if drop_hovered {
+            // Spring-loaded: open when hovered for a while during a drag.
+            let now = ui.input(|i| i.time);
+            let since = ui.data_mut(|d| *d.get_temp_mut_or_insert_with(drop_hover_id, || now));
+            let remaining = since + SPRING_LOADED_DELAY - now;
+            if !leaf && !state.is_open() {
+                if remaining <= 0.0 {
+                    state.toggle(ui);
+                    header_response.mark_changed();
+                } else {
+                    ui.ctx()
+                        .request_repaint_after(std::time::Duration::from_secs_f64(remaining));
+                }
+            }
+        } else if drop_target {
+            ui.data_mut(|d| d.remove::<f64>(drop_hover_id));
+        }
+
+        state.set_pending(open_when_ready == Some(false));
+
+        if state.is_open() != was_open {
+            if let Some(notify) = notify {
+                notify.send((id, state.is_open())).ok();
+            }
+        }
+
+        if let Some(add_contents) = context_menu {
+            header_response.context_menu(add_contents);
+        }
+
+        if let Some(disabled_reason) = disabled_reason {
+            header_response = header_response.on_disabled_hover_text(disabled_reason);
+        }
+
+        header_response.widget_info(|| {
+            let typ = if leaf {
+                WidgetType::Label
+            } else if button_style {
+                WidgetType::Button
+            } else {
+                WidgetType::CollapsingHeader
+            };
+            if typ == WidgetType::CollapsingHeader {
+                WidgetInfo::selected(typ, ui.is_enabled(), state.is_open(), galley.text())
+            } else {
+                WidgetInfo::labeled(typ, ui.is_enabled(), galley.text())
+            }
+        });
+
+        let openness = state.openness(ui.ctx());
+        if let Some(key) = galley_key {
+            let cache_id = id.with("collapsed_galley");
+            ui.data_mut(|d| {
+                if openness <= 0.0 {
+                    d.insert_temp(cache_id, (key, galley.clone()));
+                } else {
+                    d.remove::<(u64, Arc<Galley>)>(cache_id);
+                }
+            });
+        }
+        // The title to compare the edit with:
+        let title = title_buffer.as_ref().map(|_| galley.text().to_owned());
+
+        if ui.is_rect_visible(rect) {
+            let visuals = ui.style().interact_selectable(&header_response, selected);
+
+            if ui.visuals().collapsing_header_frame || show_background || button_style {
+                ui.painter().add(epaint::RectShape::new(
+                    header_response.rect.expand(visuals.expansion),
+                    frame_rounding.unwrap_or(visuals.rounding),
+                    frame_fill.unwrap_or(visuals.weak_bg_fill),
+                    frame_stroke.unwrap_or(visuals.bg_stroke),
+                ));
+            }
+
+            if selected || selectable && (header_response.hovered() || header_response.has_focus())
+            {
+                let rect = rect.expand(visuals.expansion);
+
+                ui.painter()
+                    .rect(rect, visuals.rounding, visuals.bg_fill, visuals.bg_stroke);
+            }
+
+            if leaf_dot {
+                let x = if rtl {
+                    header_response.rect.right() - ui.spacing().indent / 2.0
+                } else {
+                    header_response.rect.left() + ui.spacing().indent / 2.0
+                };
+                let center = pos2(x, header_response.rect.center().y);
+                let radius = ui.spacing().icon_width / 6.0;
+                ui.painter()
+                    .circle_filled(center, radius, visuals.fg_stroke.color);
+            } else if !leaf {
+                let (mut icon_rect, _) = ui.spacing().icon_rectangles(header_response.rect);
+                let icon_x = if trailing_icon {
+                    trailing_icon_x
+                } else if rtl {
+                    rect.right() - ui.spacing().indent / 2.0
+                } else {
+                    rect.left() + ui.spacing().indent / 2.0
+                };
+                icon_rect.set_center(pos2(icon_x, header_response.rect.center().y));
+                let icon_response = match &arrow_response {
+                    Some(arrow_response) => header_response.union(arrow_response.clone()),
+                    None => header_response.clone(),
+                };
+                let icon_response = icon_response.with_new_rect(icon_rect);
+                // Show the intent to open while pending:
+                let icon_openness = if state.is_pending() { 1.0 } else { openness };
+                if let Some(icon) = icon {
+                    icon(ui, icon_openness, &icon_response);
+                } else if let Some((texture_id, image_size)) = image_icon {
+                    let scale_range = icon_scale_range.unwrap_or(1.0..=1.0);
+                    let response = &icon_response;
+                    paint_image_icon(
+                        ui,
+                        texture_id,
+                        image_size,
+                        icon_openness,
+                        response,
+                        scale_range,
+                    );
+                } else if icon_style == CollapsingIcon::ChevronSwap {
+                    paint_chevron_swap_icon(ui, icon_openness, &icon_response);
+                } else {
+                    let scale_range = icon_scale_range.unwrap_or(0.75..=0.75);
+                    let turn = if horizontal {
+                        ArrowTurn::DownToRight
+                    } else if rtl {
+                        ArrowTurn::LeftToDown
+                    } else {
+                        ArrowTurn::RightToDown
+                    };
+                    let response = &icon_response;
+                    paint_arrow_icon(ui, icon_openness, response, scale_range, icon_color, turn);
+                }
+            }
+
+            if let Some(pin_response) = &pin_response {
+                let color = if state.is_pinned() {
+                    ui.visuals().strong_text_color()
+                } else if pin_response.hovered() {
+                    ui.visuals().text_color()
+                } else {
+                    ui.visuals().weak_text_color()
+                };
+                ui.painter().text(
+                    pin_response.rect.center(),
+                    Align2::CENTER_CENTER,
+                    "📌",
+                    FontId::proportional(ui.spacing().icon_width),
+                    color,
+                );
+            }
+
+            if modified {
+                let x = if rtl {
+                    text_pos.x - ui.spacing().item_spacing.x / 2.0
+                } else {
+                    text_pos.x + galley.size().x + ui.spacing().item_spacing.x / 2.0
+                };
+                let center = pos2(x, rect.center().y);
+                let radius =
+                    (ui.spacing().icon_width / 6.0).at_most(ui.spacing().item_spacing.x / 3.0);
+                ui.painter()
+                    .circle_filled(center, radius, ui.visuals().warn_fg_color);
+            }
+
+            if let Some(until) = highlight_new {
+                let time = ui.input(|i| i.time);
+                if time < until {
+                    // Pulse once a second:
+                    let pulse = 0.5 + 0.5 * (time * std::f64::consts::TAU).cos() as f32;
+                    let mut stroke = ui.visuals().selection.stroke;
+                    stroke.color = stroke.color.gamma_multiply(lerp(0.3..=1.0, pulse));
+                    ui.painter().rect_stroke(
+                        rect.expand(visuals.expansion),
+                        visuals.rounding,
+                        stroke,
+                    );
+                    ui.ctx().request_repaint();
+                }
+            }
+
+            if state.is_pending() {
+                let size = galley.size().y;
+                let offset = if rtl {
+                    -ui.spacing().item_spacing.x - size
+                } else {
+                    galley.size().x + ui.spacing().item_spacing.x
+                };
+                let spinner_rect =
+                    Rect::from_min_size(text_pos + vec2(offset, 0.0), vec2(size, size));
+                Spinner::new().paint_at(ui, spinner_rect);
+            }
+
+            if let Some(custom_header) = custom_header {
+                let (contents_rect, layout) = if rtl {
+                    let right = rect.right() - text_indent;
+                    let contents_rect = Rect::from_min_max(rect.min, pos2(right, rect.bottom()));
+                    (contents_rect, Layout::right_to_left(Align::Center))
+                } else {
+                    let contents_rect =
+                        Rect::from_min_max(pos2(text_pos.x, rect.top()), rect.right_bottom());
+                    (contents_rect, Layout::left_to_right(Align::Center))
+                };
+                let mut contents_ui = ui.child_ui(contents_rect, layout, None);
+                let arrow = arrow_response.as_ref().unwrap_or(&header_response);
+                custom_header(&mut contents_ui, openness, arrow);
+            } else if title.is_none() {
+                ui.painter().galley(text_pos, galley, visuals.text_color());
+            }
+
+            if let (Some(value_galley), Some(value_offset)) = (value_galley, value_offset) {
+                let value_x = if rtl {
+                    rect.right() - value_offset - value_galley.size().x
+                } else {
+                    rect.left() + value_offset
+                };
+                let value_pos = pos2(value_x, rect.center().y - value_galley.size().y / 2.0);
+                ui.painter()
+                    .galley(value_pos, value_galley, visuals.text_color());
+            }
+        }
+
+        let mut renamed_title = None;
+        if let (Some(mut buffer), Some(title)) = (title_buffer, title) {
+            let edit_rect = if rtl {
+                Rect::from_min_max(rect.min, pos2(rect.right() - text_indent, rect.bottom()))
+            } else {
+                Rect::from_min_max(pos2(text_pos.x, rect.top()), rect.right_bottom())
+            };
+            let edit_response = ui.put(
+                edit_rect,
+                TextEdit::singleline(&mut buffer)
+                    .id(title_edit_id)
+                    .font(TextStyle::Button)
+                    .desired_width(f32::INFINITY),
+            );
+            if edit_response.lost_focus() {
+                if !ui.input(|i| i.key_pressed(Key::Escape)) && title != buffer {
+                    renamed_title = Some(buffer);
+                    header_response.mark_changed();
+                }
+                ui.data_mut(|d| d.remove::<String>(title_edit_id));
+            } else {
+                ui.data_mut(|d| d.insert_temp(title_edit_id, buffer));
+            }
+        }
+
+        let applied_event = applied_event.map(|event| (event, !leaf && state.is_open()));
+        let prev_openness = if manage_state {
+            let prev_openness_id = id.with("prev_openness");
+            ui.data_mut(|d| {
+                let prev = d.get_temp(prev_openness_id).unwrap_or(openness);
+                d.insert_temp(prev_openness_id, openness);
+                prev
+            })
+        } else {
+            openness // nothing is stored
+        };
+
+        Prepared {
+            header_response,
+            arrow_response,
+            state,
+            openness,
+            prev_openness,
+            drop_hovered,
+            applied_event,
+            renamed_title,
+        }
+    }
+
+    #[inline]
+    pub fn show<R>(
+        self,
+        ui: &mut Ui,
+        add_body: impl FnOnce(&mut Ui) -> R,
+    ) -> CollapsingResponse<R> {
+        self.show_dyn(ui, Box::new(add_body), true)
+    }
+
+    /// Like [`Self::show`] with [`Self::editable_title`],
+    /// and writes a title the user committed back to `title`.
+    ///
+    /// The header shows the text given to [`Self::new`], which should usually be `title`.
+    /// Give it an [`Self::id_source`], so that renaming it doesn't change its [`Id`].
+    ///
+    /// ```
+    /// # egui::__run_test_ui(|ui| {
+    /// # let mut name = String::from("Folder");
+    /// egui::CollapsingHeader::new(name.as_str())
+    ///     .id_source("folder")
+    ///     .show_editable(ui, &mut name, |ui| ui.label("…"));
+    /// # });
+    /// ```
+    pub fn show_editable<R>(
+        self,
+        ui: &mut Ui,
+        title: &mut String,
+        add_body: impl FnOnce(&mut Ui) -> R,
+    ) -> CollapsingResponse<R> {
+        let response = self.editable_title(true).show(ui, add_body);
+        if let Some(renamed_title) = &response.renamed_title {
+            title.clone_from(renamed_title);
+        }
+        response
+    }
+
+    #[inline]
+    pub fn show_unindented<R>(
+        self,
+        ui: &mut Ui,
+        add_body: impl FnOnce(&mut Ui) -> R,
+    ) -> CollapsingResponse<R> {
+        self.show_dyn(ui, Box::new(add_body), false)
+    }
+
+    /// Like [`Self::show`], but for a body of many rows of the same height,
+    /// of which only the visible ones are shown, like [`ScrollArea::show_rows`].
+    ///
+    /// `add_rows` is given the range of rows to show, which is just the rows
+    /// inside the clip rectangle: while animating, that is only the revealed part of the body,
+    /// and inside a [`ScrollArea`], only the part in view.
+    /// The body takes up the height of all of the rows, so that is what the animation uses.
+    pub fn show_rows<R>(
+        self,
+        ui: &mut Ui,
+        row_height_sans_spacing: f32,
+        total_rows: usize,
+        add_rows: impl FnOnce(&mut Ui, std::ops::Range<usize>) -> R,
+    ) -> CollapsingResponse<R> {
+        self.show(ui, |ui| {
+            show_visible_rows(ui, row_height_sans_spacing, total_rows, add_rows)
+        })
+    }
+
+    fn body_options(&self) -> BodyOptions {
+        self.body.clone()
+    }
+
+    fn show_dyn<'c, R: 'c>(
+        mut self,
+        ui: &mut Ui,
+        add_body: Box<dyn FnOnce(&mut Ui) -> R + 'c>,
+        indented: bool,
+    ) -> CollapsingResponse<R> {
+        let leaf = self.leaf || self.non_collapsible;
+        // In a horizontal layout, the body goes to the right of the header, without indentation:
+        let horizontal = ui.layout().main_dir().is_horizontal();
+        let indented = indented && !horizontal;
+        let body_options = self.body_options();
+        let body_hover_background = self.frame.body_hover_background;
+        let animate_indent = self.animation.indent;
+        let auto_expand_on_growth = self.auto_expand_on_growth;
+        let keep_mounted = self.keep_mounted;
+        let header_body_gap = self.header_body_gap;
+        let flat_nesting = self.flat_nesting;
+        let flyout = self.flyout;
+        let sense_body_background = self.sense_body_background;
+        let cache_body = self.cache_body;
+        let lazy = self.lazy;
+        let rounded_body_clip = self
+            .frame
+            .rounded_body_clip
+            .filter(|_| ui.visuals().collapsing_header_frame || self.frame.show_background);
+        let custom_indent = self.indent;
+        let add_body: Box<dyn FnOnce(&mut Ui) -> R + 'c> = if custom_indent.is_some() {
+            let indent = ui.spacing().indent;
+            Box::new(move |ui: &mut Ui| {
+                // The widgets in the body should look as usual:
+                ui.spacing_mut().indent = indent;
+                add_body(ui)
+            })
+        } else {
+            add_body
+        };
+
+        if let Some(chip_body) = self.chip_mode {
+            return self.show_chip(ui, add_body, indented, chip_body);
+        }
+
+        let id = self.resolve_id(ui);
+        let after_body = self.after_body.take();
+        let card = self.frame.card;
+        let scroll_to_body_on_open = self.scroll_to_body_on_open;
+
+        let show_region = |ui: &mut Ui| {
+            if !self.enabled {
+                ui.disable();
+                if let Some(alpha) = self.disabled_alpha {
+                    ui.multiply_opacity(alpha);
+                }
+            }
+            if let Some(indent) = custom_indent {
+                ui.spacing_mut().indent = indent;
+            }
+
+            let Prepared {
+                header_response,
+                arrow_response,
+                mut state,
+                openness,
+                prev_openness,
+                drop_hovered,
+                applied_event,
+                renamed_title,
+            } = self.begin(ui, id); // show the header
+
+            if leaf {
+                return CollapsingResponse {
+                    header_response,
+                    toggle_response: None,
+                    body_response: None,
+                    body_returned: None,
+                    openness: 0.0,
+                    prev_openness: 0.0,
+                    drop_hovered,
+                    applied_event,
+                    renamed_title,
+                    full_content_height: None,
+                    body_background_response: None,
+                };
+            }
+
+            let id = state.id();
+            // The size of the body last time, to leave empty while animating.
+            // A vertical body is as wide as the (indented) body ui, so that is measured in there:
+            let lazy_size = if horizontal {
+                state
+                    .state
+                    .open_width
+                    .zip(state.state.open_height)
+                    .map(|(width, height)| (Some(width), height))
+            } else {
+                state.state.open_height.map(|height| (None, height))
+            };
+            let add_body: Box<dyn FnOnce(&mut Ui) -> Option<R> + 'c> =
+                if let (true, true, Some((width, height))) = (lazy, openness < 1.0, lazy_size) {
+                    Box::new(move |ui: &mut Ui| {
+                        let width = width.unwrap_or_else(|| ui.available_width());
+                        ui.allocate_space(vec2(width, height));
+                        None
+                    })
+                } else if cache_body {
+                    let fully_open = 1.0 <= openness;
+                    Box::new(move |ui: &mut Ui| cached_body(ui, id, fully_open, add_body))
+                } else {
+                    Box::new(move |ui: &mut Ui| Some(add_body(ui)))
+                };
+            let feedback = BodyFeedback::default();
+            let add_body = wrap_body(ui, id, body_options.clone(), &feedback, add_body);
+
+            let body_background = body_hover_background.map(|_| ui.painter().add(Shape::Noop));
+
+            // Interact before the body, so that its widgets are on top and get their clicks first.
+            // We use the rect of the body from the previous frame.
+            let background_id = id.with("body_background");
+            let body_background_response = if sense_body_background {
+                let rect = ui.data(|d| d.get_temp::<Rect>(background_id));
+                rect.map(|rect| ui.interact(rect, background_id, Sense::click()))
+            } else {
+                None
+            };
+
+            if let (Some(gap), false) = (header_body_gap, flyout) {
+                if 0.0 < openness {
+                    // Replace the item spacing that already follows the header:
+                    let gap = gap * openness.at_most(1.0);
+                    ui.add_space(gap - ui.spacing().item_spacing.y);
+                }
+            }
+
+            let ret_response = if auto_expand_on_growth && !state.is_open() && openness <= 0.0 {
+                expand_on_growth(ui, &mut state, add_body);
+                None
+            } else if keep_mounted && openness <= 0.0 {
+                state.store(ui.ctx()); // like `show_body_unindented` does when closed
+                show_unmounted(ui, add_body);
+                None
+            } else if flyout {
+                show_flyout(ui, id, &header_response, &mut state, openness, add_body)
+            } else if indented && flat_nesting {
+                let header_right = header_response.rect.right();
+                state.show_body_unindented(ui, |ui| flat_indent(ui, id, header_right, add_body))
+            } else if indented && animate_indent {
+                let header_right = header_response.rect.right();
+                state.show_body_unindented(ui, |ui| {
+                    sliding_indent(ui, id, header_right, openness, add_body)
+                })
+            } else if indented {
+                state.show_body_indented(&header_response, ui, add_body)
+            } else {
+                state.show_body_unindented(ui, add_body)
+            };
+            if ret_response.is_some() {
+                feedback.apply(&mut state, ui.ctx());
+            }
+            if sense_body_background {
+                ui.data_mut(|d| match &ret_response {
+                    Some(ret_response) => d.insert_temp(background_id, ret_response.response.rect),
+                    None => d.remove::<Rect>(background_id),
+                });
+            }
+
+            if let (Some(color), Some(idx), Some(ret_response)) =
+                (body_hover_background, body_background, &ret_response)
+            {
+                let rect = ret_response.response.rect;
+                if header_response.hovered() || ui.rect_contains_pointer(rect) {
+                    let rounding = ui.visuals().widgets.hovered.rounding;
+                    ui.painter()
+                        .set(idx, epaint::RectShape::filled(rect, rounding, color));
+                }
+            }
+
+            if let (Some(background), Some(ret_response)) = (rounded_body_clip, &ret_response) {
+                if openness < 1.0 {
+                    let rounding = ui.visuals().widgets.inactive.rounding;
+                    paint_corner_masks(ui, ret_response.response.rect, rounding, background);
+                }
+            }
+
+            if let (true, Some(ret_response)) = (body_options.busy, &ret_response) {
+                busy_overlay(ui, id, &ret_response.response);
+            }
+
+            if let (Some(after_body), Some(ret_response)) = (after_body, &ret_response) {
+                after_body(ui, ret_response.response.rect);
+            }
+
+            if let Some(align) = scroll_to_body_on_open {
+                if prev_openness <= 0.0 && 0.0 < openness {
+                    let rect = match &ret_response {
+                        Some(ret_response) => {
+                            header_response.rect.union(ret_response.response.rect)
+                        }
+                        None => header_response.rect, // the body isn't laid out yet
+                    };
+                    ui.scroll_to_rect(rect, Some(align));
+                }
+            }
+
+            let (body_response, body_returned) = match ret_response {
+                Some(ret_response) => (Some(ret_response.response), ret_response.inner),
+                None => (None, None),
+            };
+            CollapsingResponse {
+                header_response,
+                toggle_response: arrow_response,
+                body_response,
+                body_returned,
+                openness,
+                prev_openness,
+                drop_hovered,
+                applied_event,
+                renamed_title,
+                full_content_height: state.full_content_height(),
+                body_background_response,
+            }
+        };
+
+        // Make sure body is bellow header (or next to it, in a horizontal layout),
+        // and make sure it is one unit (necessary for putting a [`CollapsingHeader`] in a grid).
+        let show_region = |ui: &mut Ui| {
+            if horizontal {
+                ui.horizontal(show_region).inner
+            } else if ui.layout().horizontal_align() == Align::Max {
+                // Keep right-to-left:
+                ui.with_layout(Layout::top_down(Align::Max), show_region)
+                    .inner
+            } else {
+                ui.vertical(show_region).inner
+            }
+        };
+        match card {
+            // The body reports only its visible height, so the frame follows the animation:
+            Some(frame) => frame.show(ui, show_region).inner,
+            None => show_region(ui),
+        }
+    }
+
+    fn show_chip<'c, R>(
+        mut self,
+        ui: &mut Ui,
+        add_body: Box<dyn FnOnce(&mut Ui) -> R + 'c>,
         indented: bool,
+        chip_body: ChipBody,
     ) -> CollapsingResponse<R> {
-        // Make sure body is bellow header,
-        // and make sure it is one unit (necessary for putting a [`CollapsingHeader`] in a grid).
-        ui.vertical(|ui| {
-            if !self.enabled {
+        let enabled = self.enabled;
+        let disabled_alpha = self.disabled_alpha;
+        let leaf = self.leaf || self.non_collapsible;
+        let body_options = self.body_options();
+        let set_enabled = move |ui: &mut Ui| {
+            if !enabled {
                 ui.disable();
+                if let Some(alpha) = disabled_alpha {
+                    ui.multiply_opacity(alpha);
+                }
+            }
+        };
+
+        let after_body = self.after_body.take();
+
+        // Let the parent layout place (and wrap) the chip, then show the header in exactly that spot:
+        let id = self.resolve_id(ui);
+        let (_, rect) = ui.allocate_space(self.measure(ui));
+        let mut header_ui = ui.child_ui(rect, Layout::top_down(Align::Min), None);
+        set_enabled(&mut header_ui);
+        if let Some(indent) = self.indent {
+            header_ui.spacing_mut().indent = indent;
+        }
+        let Prepared {
+            header_response,
+            arrow_response,
+            mut state,
+            openness,
+            prev_openness,
+            drop_hovered,
+            applied_event,
+            renamed_title,
+        } = self.begin(&mut header_ui, id);
+
+        let id = state.id();
+        let feedback = BodyFeedback::default();
+        let ret_response = if leaf || openness <= 0.0 {
+            None
+        } else {
+            let add_body = wrap_body(ui, id, body_options.clone(), &feedback, add_body);
+            match chip_body {
+                ChipBody::Popup => {
+                    Area::new(id.with("chip_body"))
+                        .order(Order::Foreground)
+                        .fixed_pos(header_response.rect.left_bottom())
+                        .show(ui.ctx(), |ui| {
+                            Frame::popup(ui.style())
+                                .show(ui, |ui| {
+                                    set_enabled(ui);
+                                    state.show_body_unindented(ui, add_body)
+                                })
+                                .inner
+                        })
+                        .inner
+                }
+                ChipBody::BelowRow => {
+                    ui.end_row();
+                    let ret_response = ui
+                        .allocate_ui_with_layout(
+                            vec2(ui.available_width(), 0.0),
+                            Layout::top_down(Align::Min),
+                            |ui| {
+                                set_enabled(ui);
+                                if indented {
+                                    state.show_body_indented(&header_response, ui, add_body)
+                                } else {
+                                    state.show_body_unindented(ui, add_body)
+                                }
+                            },
+                        )
+                        .inner;
+                    ui.end_row();
+                    ret_response
+                }
+            }
+        };
+        if ret_response.is_some() {
+            feedback.apply(&mut state, ui.ctx());
+        }
+
+        if let (true, Some(ret_response)) = (body_options.busy, &ret_response) {
+            busy_overlay(ui, id, &ret_response.response);
+        }
+
+        if let (Some(after_body), Some(ret_response)) = (after_body, &ret_response) {
+            after_body(ui, ret_response.response.rect);
+        }
+
+        let (body_response, body_returned) = match ret_response {
+            Some(ret_response) => (Some(ret_response.response), Some(ret_response.inner)),
+            None => (None, None),
+        };
+        CollapsingResponse {
+            header_response,
+            toggle_response: arrow_response,
+            body_response,
+            body_returned,
+            openness: if leaf { 0.0 } else { openness },
+            prev_openness: if leaf { 0.0 } else { prev_openness },
+            drop_hovered,
+            applied_event,
+            renamed_title,
+            full_content_height: if leaf {
+                None
+            } else {
+                state.full_content_height()
+            },
+            body_background_response: None,
+        }
+    }
+}
+
+/// The options of a [`CollapsingHeader`] for its icon.
+struct IconOptions {
+    painter: Option<IconPainter>,
+    style: CollapsingIcon,
+    side: HeaderIconSide,
+    scale_range: Option<RangeInclusive<f32>>,
+    color: Option<Color32>,
+    image: Option<(TextureId, Vec2)>,
+}
+
+/// The options of a [`CollapsingHeader`] for its frame and backgrounds.
+struct FrameOptions {
+    show_background: bool,
+    fill: Option<Color32>,
+    stroke: Option<Stroke>,
+    rounding: Option<Rounding>,
+    card: Option<Frame>,
+    rounded_body_clip: Option<Color32>,
+    body_hover_background: Option<Color32>,
+}
+
+/// The options of a [`CollapsingHeader`] for how it animates.
+struct AnimationOptions {
+    header_height: bool,
+    id: Option<Id>,
+    openness_override: Option<f32>,
+    indent: bool,
+    reveal_style: RevealStyle,
+    min_height: f32,
+    fade_edge: f32,
+    style: AnimationStyle,
+    time: Option<f32>,
+}
+
+/// The options of a [`CollapsingHeader`] for its body, which [`wrap_body`] needs.
+#[derive(Clone)]
+struct BodyOptions {
+    enabled: bool,
+    max_height: Option<f32>,
+    show_more_footer: Option<FooterText>,
+    peek_height: Option<f32>,
+    peek_more_text: WidgetText,
+    busy: bool,
+}
+
+/// The shapes painted by a body, see [`CollapsingHeader::cache_body`].
+#[derive(Clone)]
+struct BodyCache {
+    /// Where the body started.
+    origin: Pos2,
+    size: Vec2,
+
+    /// Only replayed with the same layout inputs:
+    available_width: f32,
+    pixels_per_point: f32,
+    style: Arc<Style>,
+
+    shapes: Arc<Vec<ClippedShape>>,
+}
+
+impl BodyCache {
+    /// Was this recorded with the same width, scale and style as `ui` has now?
+    fn fits(&self, ui: &Ui, available_width: f32) -> bool {
+        self.available_width == available_width
+            && self.pixels_per_point == ui.ctx().pixels_per_point()
+            && (Arc::ptr_eq(&self.style, ui.style()) || *self.style == **ui.style())
+    }
+}
+
+/// Replay the shapes of the body from an earlier frame, or show it and record them.
+///
+/// Returns `None` when replaying.
+fn cached_body<R>(
+    ui: &mut Ui,
+    id: Id,
+    fully_open: bool,
+    add_body: impl FnOnce(&mut Ui) -> R,
+) -> Option<R> {
+    let cache_id = id.with("body_cache");
+    if !fully_open {
+        // Animating: the clip rectangles change every frame, so don't record them.
+        ui.data_mut(|d| d.remove::<BodyCache>(cache_id));
+        return Some(add_body(ui));
+    }
+
+    let origin = ui.cursor().min;
+    let available_width = ui.available_width();
+    let cache = ui.data(|d| d.get_temp::<BodyCache>(cache_id));
+    if let Some(cache) = cache.filter(|cache| cache.fits(ui, available_width)) {
+        let delta = origin - cache.origin;
+        let clip_rect = ui.clip_rect();
+        let shapes = cache.shapes.iter().map(|clipped| {
+            let mut shape = clipped.shape.clone();
+            shape.translate(delta);
+            ClippedShape {
+                clip_rect: clipped.clip_rect.translate(delta).intersect(clip_rect),
+                shape,
+            }
+        });
+        ui.ctx().graphics_mut(|g| {
+            let list = g.entry(ui.layer_id());
+            for ClippedShape { clip_rect, shape } in shapes {
+                list.add(clip_rect, shape);
+            }
+        });
+        ui.allocate_exact_size(cache.size, Sense::hover());
+        return None;
+    }
+
+    let layer_id = ui.layer_id();
+    let shape_count = |ctx: &Context| {
+        ctx.graphics(|g| g.get(layer_id).map_or(0, |list| list.all_entries().len()))
+    };
+    let first_shape = shape_count(ui.ctx());
+    let ret = add_body(ui);
+    let shapes = ui.ctx().graphics(|g| {
+        g.get(layer_id).map_or_else(Vec::new, |list| {
+            list.all_entries().skip(first_shape).cloned().collect()
+        })
+    });
+    let size = ui.min_rect().max - origin;
+    if !ui
+        .clip_rect()
+        .contains_rect(Rect::from_min_size(origin, size))
+    {
+        // Widgets outside the clip rectangle (e.g. scrolled out of view) don't paint,
+        // so replaying these shapes would leave gaps once they come into view:
+        ui.data_mut(|d| d.remove::<BodyCache>(cache_id));
+        return Some(ret);
+    }
+    let cache = BodyCache {
+        origin,
+        size,
+        available_width,
+        pixels_per_point: ui.ctx().pixels_per_point(),
+        style: ui.style().clone(),
+        shapes: Arc::new(shapes),
+    };
+    ui.data_mut(|d| d.insert_temp(cache_id, cache));
+    Some(ret)
+}
+
+/// Cover the body with a translucent overlay and a spinner, see [`CollapsingHeader::body_busy`].
+fn busy_overlay(ui: &Ui, id: Id, body: &Response) {
+    if body.layer_id != ui.layer_id() {
+        return; // e.g. a popup chip body, which is on top of us
+    }
+    let rect = body.rect;
+    // On top of the widgets of the body, so they don't get hovered or clicked:
+    ui.interact(rect, id.with("busy_overlay"), Sense::click_and_drag());
+    let bg = ui.visuals().panel_fill.gamma_multiply(0.6);
+    ui.painter().rect_filled(rect, 0.0, bg);
+    let size = ui.spacing().interact_size.y.at_most(rect.height());
+    Spinner::new().paint_at(ui, Rect::from_center_size(rect.center(), Vec2::splat(size)));
+}
+
+/// Paint a gradient from transparent at the top of `rect` to the panel color at the bottom.
+fn paint_fade_out(ui: &Ui, rect: Rect) {
+    let color = ui.visuals().panel_fill;
+    let mut mesh = Mesh::default();
+    mesh.colored_vertex(rect.left_top(), Color32::TRANSPARENT);
+    mesh.colored_vertex(rect.right_top(), Color32::TRANSPARENT);
+    mesh.colored_vertex(rect.left_bottom(), color);
+    mesh.colored_vertex(rect.right_bottom(), color);
+    mesh.add_triangle(0, 1, 2);
+    mesh.add_triangle(1, 2, 3);
+    ui.painter().add(mesh);
+}
+
+/// Lay out the body of a closed header invisibly, and open the header if the body has grown.
+///
+/// See [`CollapsingHeader::auto_expand_on_growth`].
+fn expand_on_growth<R>(
+    ui: &mut Ui,
+    state: &mut CollapsingState,
+    add_body: impl FnOnce(&mut Ui) -> R,
+) {
+    let mut measure_ui = ui.child_ui(ui.available_rect_before_wrap(), *ui.layout(), None);
+    measure_ui.set_invisible();
+    add_body(&mut measure_ui);
+    let height = measure_ui.min_rect().height();
+
+    let grown = state
+        .state
+        .last_content_height
+        .map_or(false, |last| height > last + 0.5);
+    state.state.last_content_height = Some(height);
+    if grown {
+        state.set_open(true);
+        ui.ctx().request_repaint();
+    }
+    state.store(ui.ctx());
+}
+
+/// Show the body of a closed header invisibly, without taking up any space.
+///
+/// Returns the height the body would have had.
+/// See [`CollapsingHeader::keep_mounted`].
+fn show_unmounted<R>(ui: &mut Ui, add_body: impl FnOnce(&mut Ui) -> R) -> f32 {
+    let mut rect = ui.available_rect_before_wrap();
+    rect.max.y = rect.min.y;
+    let mut hidden_ui = ui.child_ui(rect, *ui.layout(), None);
+    hidden_ui.set_invisible();
+    hidden_ui.set_clip_rect(rect);
+    add_body(&mut hidden_ui);
+    hidden_ui.min_rect().height()
+}
+
+/// What [`wrap_body`] found out while showing the body, to be applied to the state afterwards.
+#[derive(Default)]
+struct BodyFeedback {
+    /// The user asked to see all of a peeking body.
+    show_full: Cell<bool>,
+
+    /// The height of the body before capping it to [`BodyOptions::max_height`].
+    full_content_height: Cell<Option<f32>>,
+}
+
+impl BodyFeedback {
+    fn apply(&self, state: &mut CollapsingState, ctx: &Context) {
+        if self.show_full.get() {
+            state.set_fully_open(true);
+        }
+        state.state.full_content_height = self.full_content_height.get();
+        state.state.last_content_height = state.state.open_height;
+        state.store(ctx);
+    }
+}
+
+/// Register the body as a child of `id`, disable it, and cap its height if requested.
+fn wrap_body<'c, R: 'c>(
+    ui: &Ui,
+    id: Id,
+    options: BodyOptions,
+    feedback: &'c BodyFeedback,
+    add_body: Box<dyn FnOnce(&mut Ui) -> R + 'c>,
+) -> Box<dyn FnOnce(&mut Ui) -> R + 'c> {
+    let add_body: Box<dyn FnOnce(&mut Ui) -> R + 'c> = Box::new(move |ui: &mut Ui| {
+        HeaderRegistry::push_parent(ui.ctx(), id);
+        if !options.enabled {
+            ui.disable();
+        }
+        let ret = add_body(ui);
+        HeaderRegistry::pop_parent(ui.ctx());
+        ret
+    });
+
+    let add_body = match options.peek_height {
+        Some(peek_height) => {
+            let fully_open =
+                CollapsingState::load(ui.ctx(), id).map_or(false, |s| s.is_fully_open());
+            Box::new(move |ui: &mut Ui| {
+                peek_body(
+                    ui,
+                    id,
+                    peek_height,
+                    options.peek_more_text,
+                    fully_open,
+                    &feedback.show_full,
+                    add_body,
+                )
+            })
+        }
+        None => add_body,
+    };
+
+    let uncapped_id = id.with("uncapped");
+    let uncapped = ui.data(|d| d.get_temp(uncapped_id)).unwrap_or(false);
+    match options.max_height {
+        Some(max_height) if !uncapped => Box::new(move |ui: &mut Ui| {
+            let output = ScrollArea::vertical()
+                .id_source(uncapped_id)
+                .max_height(max_height)
+                .auto_shrink([false, true])
+                .show(ui, add_body);
+            feedback
+                .full_content_height
+                .set(Some(output.content_size.y));
+            let hidden_height = output.content_size.y - output.inner_rect.height();
+            if let Some(footer_text) = &options.show_more_footer {
+                if 0.5 <= hidden_height && ui.link(footer_text(hidden_height)).clicked() {
+                    ui.data_mut(|d| d.insert_temp(uncapped_id, true));
+                }
+            }
+            output.inner
+        }),
+        _ => add_body,
+    }
+}
+
+/// Show the first `peek_height` of the body, or animate to all of it if `fully_open`.
+fn peek_body<R>(
+    ui: &mut Ui,
+    id: Id,
+    peek_height: f32,
+    more_text: WidgetText,
+    fully_open: bool,
+    show_full: &Cell<bool>,
+    add_body: impl FnOnce(&mut Ui) -> R,
+) -> R {
+    let full_openness = ui
+        .ctx()
+        .animate_bool_responsive(id.with("peek_full"), fully_open);
+    let content_height_id = id.with("peek_content_height");
+    let last_content_height = ui
+        .data(|d| d.get_temp::<f32>(content_height_id))
+        .unwrap_or(peek_height);
+    let max_height = if 1.0 <= full_openness {
+        f32::INFINITY
+    } else {
+        lerp(
+            peek_height..=last_content_height.max(peek_height),
+            full_openness,
+        )
+    };
+
+    let mut child_ui = ui.child_ui(ui.available_rect_before_wrap(), *ui.layout(), None);
+    let top = child_ui.max_rect().top();
+    let mut clip_rect = child_ui.clip_rect();
+    clip_rect.max.y = clip_rect.max.y.at_most(top + max_height);
+    child_ui.set_clip_rect(clip_rect);
+    let ret = add_body(&mut child_ui);
+
+    let content_rect = child_ui.min_rect();
+    ui.data_mut(|d| d.insert_temp(content_height_id, content_rect.height()));
+    let mut shown_rect = content_rect;
+    shown_rect.max.y = shown_rect.max.y.at_most(top + max_height);
+    ui.allocate_rect(shown_rect, Sense::hover());
+
+    if shown_rect.height() < content_rect.height() {
+        // Fade out the bottom, to show that there is more:
+        let fade_height = ui.spacing().interact_size.y.at_most(shown_rect.height());
+        let fade_rect = Rect::from_min_max(
+            pos2(shown_rect.left(), shown_rect.bottom() - fade_height),
+            shown_rect.max,
+        );
+        paint_fade_out(ui, fade_rect);
+
+        if !fully_open && ui.link(more_text).clicked() {
+            show_full.set(true);
+        }
+    }
+
+    ret
+}
+
+/// Paint over the parts of the bottom corners of `rect` that are outside of its `rounding`.
+fn paint_corner_masks(ui: &Ui, rect: Rect, rounding: Rounding, color: Color32) {
+    let max_radius = 0.5 * rect.width().min(rect.height());
+    let corners = [
+        (rect.left_bottom(), rounding.sw, vec2(1.0, -1.0)),
+        (rect.right_bottom(), rounding.se, vec2(-1.0, -1.0)),
+    ];
+    for (corner, radius, inward) in corners {
+        let radius = radius.min(max_radius);
+        if radius <= 0.0 {
+            continue;
+        }
+
+        // A fan of triangles from the corner to the rounded edge:
+        let center = corner + radius * inward;
+        let mut mesh = Mesh::default();
+        mesh.colored_vertex(corner, color);
+        let n = 8;
+        for i in 0..=n {
+            let angle = remap(i as f32, 0.0..=n as f32, 0.0..=std::f32::consts::FRAC_PI_2);
+            let offset = vec2(-inward.x * angle.cos(), -inward.y * angle.sin());
+            mesh.colored_vertex(center + radius * offset, color);
+            if 0 < i {
+                mesh.add_triangle(0, i, i + 1);
+            }
+        }
+        ui.painter().add(mesh);
+    }
+}
+
+/// Indent the body by [`CollapsingHeader::FLAT_NESTING_INDENT`], with a guide line.
+fn flat_indent<R>(
+    ui: &mut Ui,
+    id: Id,
+    header_right: f32,
+    add_body: impl FnOnce(&mut Ui) -> R,
+) -> R {
+    let indent = ui.spacing().indent;
+    let left_vline = ui.visuals().indent_has_left_vline;
+    ui.spacing_mut().indent = CollapsingHeader::FLAT_NESTING_INDENT;
+    ui.visuals_mut().indent_has_left_vline = true;
+    let ret = ui
+        .indent(id, |ui| {
+            // The headers in the body should look as usual:
+            ui.spacing_mut().indent = indent;
+            ui.visuals_mut().indent_has_left_vline = left_vline;
+            ui.expand_to_include_x(header_right);
+            add_body(ui)
+        })
+        .inner;
+    ui.spacing_mut().indent = indent;
+    ui.visuals_mut().indent_has_left_vline = left_vline;
+    ret
+}
+
+/// Like [`Ui::indent`], but from the right, for a right-to-left layout.
+fn indent_from_right<R>(ui: &mut Ui, id: Id, add_body: impl FnOnce(&mut Ui) -> R) -> R {
+    let mut child_rect = ui.available_rect_before_wrap();
+    child_rect.max.x -= ui.spacing().indent;
+    let mut child_ui = ui.child_ui_with_id_source(child_rect, *ui.layout(), id, None);
+    let ret = add_body(&mut child_ui);
+    let min_rect = child_ui.min_rect();
+    ui.allocate_rect(min_rect.with_max_x(ui.max_rect().right()), Sense::hover());
+    ret
+}
+
+/// Indent the body by `openness` of the full indentation, without changing its width.
+fn sliding_indent<R>(
+    ui: &mut Ui,
+    id: Id,
+    header_right: f32,
+    openness: f32,
+    add_body: impl FnOnce(&mut Ui) -> R,
+) -> R {
+    let offset = ui.spacing().indent * (1.0 - openness);
+    let rect = ui.available_rect_before_wrap().translate(-offset * Vec2::X);
+    let mut child_ui = ui.child_ui(rect, *ui.layout(), None);
+    let ret = child_ui
+        .indent(id, |ui| {
+            // make as wide as the header:
+            ui.expand_to_include_x(header_right - offset);
+            add_body(ui)
+        })
+        .inner;
+
+    let mut min_rect = child_ui.min_rect();
+    min_rect.min.x += offset; // don't claim space to the left of the body
+    ui.allocate_rect(min_rect, Sense::hover());
+    ret
+}
+
+/// The response from showing a [`CollapsingHeader`].
+pub struct CollapsingResponse<R> {
+    /// Response of the actual clickable header.
+    pub header_response: Response,
+
+    /// Response of the arrow at the start of the header.
+    ///
+    /// The arrow is on top of the start of [`Self::header_response`],
+    /// so interactions with the arrow (e.g. a right-click to show a context menu)
+    /// are reported here and not in [`Self::header_response`].
+    /// Clicking either of them toggles the header.
+    ///
+    /// `None` for a [`CollapsingHeader::leaf`] or [`CollapsingHeader::non_collapsible`],
+    /// which have no arrow. See also [`Self::toggle_or_header_response`].
+    ///
+    /// For e.g. a file tree, where clicking the row selects it and only the arrow opens it:
+    /// ```
+    /// # egui::__run_test_ui(|ui| {
+    /// # let mut selected = false;
+    /// let response = egui::CollapsingHeader::new("src")
+    ///     .click_toggles(false)
+    ///     .show(ui, |ui| ui.label("main.rs"));
+    /// if response.header_response.clicked() {
+    ///     selected = true;
+    /// }
+    /// if response.toggle_response.as_ref().is_some_and(|arrow| arrow.clicked()) {
+    ///     let id = response.header_response.id;
+    ///     if let Some(mut state) = egui::collapsing_header::CollapsingState::load(ui.ctx(), id) {
+    ///         state.toggle(ui);
+    ///         state.store(ui.ctx());
+    ///     }
+    /// }
+    /// # });
+    /// ```
+    pub toggle_response: Option<Response>,
+
+    /// None iff collapsed.
+    pub body_response: Option<Response>,
+
+    /// None if collapsed, or if the body was replayed by [`CollapsingHeader::cache_body`].
+    pub body_returned: Option<R>,
+
+    /// 0.0 if fully closed, 1.0 if fully open, and something in-between while animating.
+    pub openness: f32,
+
+    /// The [`Self::openness`] the previous time the header was shown.
+    ///
+    /// See [`Self::just_finished_opening`] and [`Self::just_finished_closing`].
+    pub prev_openness: f32,
+
+    /// Is something being dragged over this [`CollapsingHeader::drop_target`]?
+    pub drop_hovered: bool,
+
+    /// The event from the [`CollapsingHeader::display_queue`] that was applied this frame, if any,
+    /// and whether the header is open after it.
+    ///
+    /// Leaves can't open, so they stay closed.
+    pub applied_event: Option<(CollapsingEvent, bool)>,
+
+    /// The new title the user committed this frame, see [`CollapsingHeader::editable_title`].
+    pub renamed_title: Option<String>,
+
+    /// The height of all of the body, including any parts hidden by
+    /// [`CollapsingHeader::max_body_height`] or the animation.
+    ///
+    /// See [`CollapsingState::full_content_height`].
+    pub full_content_height: Option<f32>,
+
+    /// The empty space of the body, with [`CollapsingHeader::sense_body_background`].
+    ///
+    /// It is behind the widgets of the body, so it only gets the clicks they don't.
+    /// It covers the body as it was in the previous frame.
+    pub body_background_response: Option<Response>,
+}
+
+impl<R> CollapsingResponse<R> {
+    /// [`Self::toggle_response`], or [`Self::header_response`] if there is no separate arrow.
+    pub fn toggle_or_header_response(&self) -> &Response {
+        self.toggle_response
+            .as_ref()
+            .unwrap_or(&self.header_response)
+    }
+
+    /// The header and body combined, e.g. to check if either is hovered.
+    ///
+    /// The rect is the union of the header and body rects,
+    /// and [`Response::hovered`], [`Response::clicked`] etc. are true for either.
+    /// When collapsed, this is just the [`Self::header_response`].
+    ///
+    /// This is computed with [`Response::union`], not an actual interaction,
+    /// so use it for hover detection and visuals, not for allocating space.
+    /// A body on another layer (see [`ChipBody::Popup`]) is not included.
+    pub fn union_response(&self) -> Response {
+        match &self.body_response {
+            Some(body) if body.layer_id == self.header_response.layer_id => {
+                self.header_response.union(body.clone())
+            }
+            _ => self.header_response.clone(),
+        }
+    }
+
+    /// A value that changes every time the header is opened or closed,
+    /// and is stable between frames otherwise.
+    ///
+    /// See [`CollapsingState::change_token`].
+    pub fn change_token(&self) -> u32 {
+        let ctx = &self.header_response.ctx;
+        CollapsingState::load(ctx, self.header_response.id).map_or(0, |state| state.change_token())
+    }
+
+    /// Is the [`CollapsingHeader::context_menu`] of the header open?
+    pub fn context_menu_opened(&self) -> bool {
+        self.header_response.context_menu_opened()
+    }
+
+    /// Does the header have keyboard focus, e.g. for painting a custom focus ring?
+    ///
+    /// Like all clickable widgets, the header is in the tab order,
+    /// so it can be reached with tab and toggled with space or enter.
+    /// A [`CollapsingHeader::header_sense`] that doesn't sense clicks takes it out of the tab order.
+    ///
+    /// The arrow is not in the tab order (it used to be a second tab stop for each header),
+    /// so pressing tab goes straight from one header to the next.
+    pub fn header_has_focus(&self) -> bool {
+        self.header_response.has_focus()
+    }
+
+    /// Was the header double-clicked, e.g. to open or close all of the headers inside it?
+    ///
+    /// Each of the two clicks still toggles the header as usual.
+    ///
+    /// ```
+    /// # egui::__run_test_ui(|ui| {
+    /// use egui::collapsing_header::CollapsingState;
+    ///
+    /// let response = egui::CollapsingHeader::new("src")
+    ///     .show(ui, |ui| egui::CollapsingHeader::new("bin").show(ui, |_| {}));
+    /// if response.header_double_clicked() {
+    ///     let id = response.header_response.id;
+    ///     let open = CollapsingState::load(ui.ctx(), id).map_or(false, |state| state.is_open());
+    ///     CollapsingState::set_subtree_open(ui.ctx(), id, open);
+    /// }
+    /// # });
+    /// ```
+    pub fn header_double_clicked(&self) -> bool {
+        self.header_response.double_clicked()
+    }
+
+    /// Was the empty space of the body clicked, rather than one of its widgets?
+    ///
+    /// Requires [`CollapsingHeader::sense_body_background`].
+    pub fn body_background_clicked(&self) -> bool {
+        self.body_background_response
+            .as_ref()
+            .map_or(false, |response| response.clicked())
+    }
+
+    /// Was the [`CollapsingHeader`] fully closed (and not being animated)?
+    pub fn fully_closed(&self) -> bool {
+        self.openness <= 0.0
+    }
+
+    /// Was the [`CollapsingHeader`] fully open (and not being animated)?
+    pub fn fully_open(&self) -> bool {
+        self.openness >= 1.0
+    }
+
+    /// Did the [`CollapsingHeader`] finish opening this frame?
+    ///
+    /// Unlike [`Self::fully_open`], this is only true for one frame,
+    /// e.g. for doing something once the body is all visible.
+    /// Also true when opening without an animation,
+    /// and only once when an [`AnimationStyle::Overshoot`] goes past fully open.
+    /// Always `false` with [`CollapsingHeader::manage_state`] turned off, since the previous openness isn't stored.
+    pub fn just_finished_opening(&self) -> bool {
+        self.prev_openness < 1.0 && 1.0 <= self.openness
+    }
+
+    /// Did the [`CollapsingHeader`] finish closing this frame?
+    ///
+    /// Like [`Self::just_finished_opening`], but for [`Self::fully_closed`].
+    pub fn just_finished_closing(&self) -> bool {
+        0.0 < self.prev_openness && self.openness <= 0.0
+    }
+}
+
+// ----------------------------------------------------------------------------
+
+/// A minimal disclosure, like `<details><summary>` in HTML:
+/// a short summary with a small arrow, that shows the details below it when clicked.
+///
+/// There is no frame or background (regardless of [`Visuals::collapsing_header_frame`]),
+/// and the spacing is tight, so it fits in with surrounding text.
+/// For anything more, use a [`CollapsingHeader`].
+/// The summary text is used as the id source, so it must be unique within the [`Ui`].
+///
+/// ```
+/// # egui::__run_test_ui(|ui| {
+/// ui.label("The build failed.");
+/// let response = egui::summary_details(ui, "Show log", |ui| {
+///     ui.monospace("error: expected `;`");
+/// });
+/// if response.fully_closed() {
+///     ui.label("Open the log for details.");
+/// }
+/// # });
+/// ```
+pub fn summary_details<R>(
+    ui: &mut Ui,
+    summary: impl Into<WidgetText>,
+    add_details: impl FnOnce(&mut Ui) -> R,
+) -> CollapsingResponse<R> {
+    ui.scope(|ui| {
+        ui.visuals_mut().collapsing_header_frame = false;
+        CollapsingHeader::new(summary)
+            .compact(true)
+            .icon_scale_range(0.5..=0.5)
+            .header_body_gap(0.0)
+            .show(ui, add_details)
+    })
+    .inner
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{click, run_frame, run_input, run_ui};
+
+    #[test]
+    fn measure_matches_allocated_size() {
+        let ctx = Context::default();
+        for frame in [false, true] {
+            run_ui(&ctx, |ui| {
+                ui.visuals_mut().collapsing_header_frame = frame;
+                let measured = CollapsingHeader::new("Some header text").measure(ui);
+                let response = CollapsingHeader::new("Some header text").show(ui, |_| {});
+                assert_eq!(measured, response.header_response.rect.size());
+            });
+        }
+    }
+
+    #[test]
+    fn disabled_reason_shows_on_hover() {
+        let ctx = Context::default();
+        ctx.style_mut(|style| style.interaction.tooltip_delay = 0.0);
+        let header = || {
+            CollapsingHeader::new("Locked")
+                .enabled(false)
+                .disabled_reason("Finish step one first")
+        };
+
+        let mut rect = Rect::NOTHING;
+        run_ui(&ctx, |ui| {
+            rect = header().show(ui, |_| {}).header_response.rect;
+        });
+
+        let mut tooltip_open = false;
+        for _ in 0..3 {
+            run_frame(&ctx, vec![Event::PointerMoved(rect.center())], |ui| {
+                tooltip_open = header().show(ui, |_| {}).header_response.is_tooltip_open();
+            });
+        }
+        assert!(tooltip_open);
+    }
+
+    #[test]
+    fn disabled_alpha_fades_the_body() {
+        let body_opacity = |alpha: f32| {
+            let ctx = Context::default();
+            let mut opacity = None;
+            run_ui(&ctx, |ui| {
+                CollapsingHeader::new("Locked")
+                    .default_open(true)
+                    .enabled(false)
+                    .disabled_alpha(alpha)
+                    .show(ui, |ui| opacity = Some(ui.opacity()));
+            });
+            opacity.unwrap()
+        };
+        assert_eq!(body_opacity(0.5), 0.5);
+        assert_eq!(body_opacity(2.0), 1.0);
+        assert_eq!(body_opacity(-1.0), 0.0);
+    }
+
+    #[test]
+    fn anim_id_decouples_animation_from_storage() {
+        let ctx = Context::default();
+        let header = |anim_id: &str| {
+            CollapsingHeader::new("Shared")
+                .id_source("shared")
+                .anim_id(Id::new(anim_id))
+        };
+        let mut id = Id::NULL;
+        run_ui(&ctx, |ui| {
+            id = header("a").show(ui, |_| {}).header_response.id;
+            header("b").show(ui, |_| {});
+        });
+
+        // Open the shared state, but only show the first instance:
+        let mut state = CollapsingState::load(&ctx, id).unwrap();
+        state.set_open(true);
+        state.store(&ctx);
+        run_ui(&ctx, |ui| {
+            header("a").show(ui, |_| {});
+        });
+
+        let mut openness = (0.0, 0.0);
+        run_ui(&ctx, |ui| {
+            openness.0 = header("a").show(ui, |_| {}).openness;
+            openness.1 = header("b").show(ui, |_| {}).openness;
+        });
+        assert!(0.0 < openness.0 && openness.0 < 1.0, "{openness:?}");
+        assert_ne!(openness.0, openness.1);
+    }
+
+    #[test]
+    fn children_order_is_stable() {
+        let ctx = Context::default();
+        let mut ids = vec![];
+        for _ in 0..3 {
+            run_ui(&ctx, |ui| {
+                ids.clear();
+                CollapsingHeader::new("parent")
+                    .default_open(true)
+                    .show(ui, |ui| {
+                        for name in ["c", "a", "b"] {
+                            ids.push(
+                                CollapsingHeader::new(name)
+                                    .show(ui, |_| {})
+                                    .header_response
+                                    .id,
+                            );
+                        }
+                    });
+            });
+        }
+
+        let top = CollapsingState::children_of(&ctx, Id::NULL);
+        assert_eq!(top.len(), 1);
+        assert_eq!(CollapsingState::children_of(&ctx, top[0]), ids);
+        assert!(CollapsingState::children_of(&ctx, ids[0]).is_empty());
+    }
+
+    #[test]
+    fn compact_header_is_shorter() {
+        let ctx = Context::default();
+        run_ui(&ctx, |ui| {
+            let normal = CollapsingHeader::new("Header").show(ui, |_| {});
+            let compact = CollapsingHeader::new("Header")
+                .compact(true)
+                .show(ui, |_| {});
+            let normal_height = normal.header_response.rect.height();
+            let compact_height = compact.header_response.rect.height();
+            assert!(compact_height < normal_height);
+            assert!(ui.spacing().icon_width <= compact_height);
+        });
+    }
+
+    #[test]
+    fn leaf_text_aligns_with_parent_title() {
+        let ctx = Context::default();
+        let mut x = (0.0, 0.0);
+        run_ui(&ctx, |ui| {
+            let group = CollapsingHeader::new("group")
+                .default_open(true)
+                .show(ui, |ui| {
+                    let leaf = CollapsingHeader::new("leaf").leaf(true).show(ui, |_| {});
+                    assert!(leaf.body_response.is_none());
+                    leaf.header_response.rect.left()
+                });
+            let group_text_x = group.header_response.rect.left() + ui.spacing().indent;
+            x = (group_text_x, group.body_returned.unwrap());
+        });
+        assert_eq!(x.0, x.1);
+    }
+
+    #[test]
+    fn notify_sends_on_transitions_only() {
+        let ctx = Context::default();
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let mut id = Id::NULL;
+        for open in [false, true, true, false, false] {
+            run_ui(&ctx, |ui| {
+                id = CollapsingHeader::new("Header")
+                    .open(Some(open))
+                    .notify(sender.clone())
+                    .show(ui, |_| {})
+                    .header_response
+                    .id;
+            });
+        }
+        assert_eq!(
+            receiver.try_iter().collect::<Vec<_>>(),
+            [(id, true), (id, false)]
+        );
+    }
+
+    #[test]
+    fn alt_click_toggles_descendants() {
+        fn show(ctx: &Context, events: Vec<Event>) -> (Rect, Id) {
+            let mut out = (Rect::NOTHING, Id::NULL);
+            let input = RawInput {
+                events,
+                modifiers: Modifiers::ALT,
+                ..Default::default()
+            };
+            run_input(ctx, input, |ui| {
+                out.0 = CollapsingHeader::new("root")
+                    .default_open(true)
+                    .alt_click_recursive(true)
+                    .show(ui, |ui| {
+                        CollapsingHeader::new("child")
+                            .default_open(true)
+                            .show(ui, |ui| {
+                                out.1 = ui.collapsing("grandchild", |_| {}).header_response.id;
+                            });
+                    })
+                    .header_response
+                    .rect;
+            });
+            out
+        }
+
+        let ctx = Context::default();
+        show(&ctx, vec![]);
+        let (rect, grandchild) = show(&ctx, vec![]);
+        let root = CollapsingState::children_of(&ctx, Id::NULL)[0];
+        let child = CollapsingState::children_of(&ctx, root)[0];
+        assert!(!CollapsingState::load(&ctx, grandchild).unwrap().is_open());
+
+        let pos = rect.center();
+        let click = |pressed| Event::PointerButton {
+            pos,
+            button: PointerButton::Primary,
+            pressed,
+            modifiers: Modifiers::ALT,
+        };
+
+        // The root was open, so an alt-click closes it and everything inside it:
+        show(&ctx, vec![Event::PointerMoved(pos), click(true)]);
+        show(&ctx, vec![click(false)]);
+        for id in [root, child, grandchild] {
+            assert!(!CollapsingState::load(&ctx, id).unwrap().is_open());
+        }
+
+        show(&ctx, vec![click(true)]);
+        show(&ctx, vec![click(false)]);
+        for id in [root, child, grandchild] {
+            assert!(CollapsingState::load(&ctx, id).unwrap().is_open());
+        }
+    }
+
+    #[test]
+    fn openness_override_controls_body_height() {
+        let ctx = Context::default();
+        let mut body_height = 0.0;
+        for openness in [None, Some(0.5)] {
+            run_ui(&ctx, |ui| {
+                let response = CollapsingHeader::new("Header")
+                    .default_open(true)
+                    .openness_override(openness)
+                    .show(ui, |ui| ui.allocate_space(vec2(10.0, 100.0)));
+                body_height = response.body_response.unwrap().rect.height();
+            });
+        }
+        assert_eq!(body_height, 50.0);
+    }
+
+    #[test]
+    fn openness_override_is_clamped() {
+        let ctx = Context::default();
+        let show = |openness: f32| {
+            let mut result = None;
+            run_ui(&ctx, |ui| {
+                let mut shown = false;
+                let response = CollapsingHeader::new("Header")
+                    .default_open(true)
+                    .openness_override(Some(openness))
+                    .show(ui, |ui| {
+                        shown = true;
+                        ui.allocate_space(vec2(10.0, 100.0));
+                    });
+                result = Some((shown, response.openness));
+            });
+            result.unwrap()
+        };
+
+        assert_eq!(show(1.5), (true, 1.0));
+        assert_eq!(show(0.0), (false, 0.0));
+        assert_eq!(show(-1.0), (false, 0.0));
+    }
+
+    #[test]
+    fn open_duration_grows_while_open() {
+        let ctx = Context::default();
+        let mut durations = vec![];
+        for open in [false, true, true, true, false] {
+            run_ui(&ctx, |ui| {
+                let id = CollapsingHeader::new("Header")
+                    .open(Some(open))
+                    .show(ui, |_| {})
+                    .header_response
+                    .id;
+                let state = CollapsingState::load(ui.ctx(), id).unwrap();
+                durations.push(state.open_duration(ui.ctx()));
+            });
+        }
+        assert_eq!(durations[0], None);
+        assert_eq!(durations[1], Some(0.0));
+        assert!(durations[1] < durations[2] && durations[2] < durations[3]);
+        assert_eq!(durations[4], None);
+    }
+
+    #[test]
+    fn clamp_body_rect() {
+        for (clamp, expected_height) in [(true, 50.0), (false, 100.0)] {
+            let ctx = Context::default();
+            let mut body_height = 0.0;
+            for openness in [None, Some(0.5)] {
+                run_ui(&ctx, |ui| {
+                    let response = CollapsingHeader::new("Header")
+                        .default_open(true)
+                        .openness_override(openness)
+                        .clamp_body_rect(clamp)
+                        .show(ui, |ui| ui.allocate_space(vec2(10.0, 100.0)));
+                    body_height = response.body_response.unwrap().rect.height();
+                });
+            }
+            assert_eq!(body_height, expected_height);
+        }
+    }
+
+    #[test]
+    fn chip_mode_in_wrapped_layout() {
+        for chip_body in [ChipBody::Popup, ChipBody::BelowRow] {
+            let ctx = Context::default();
+            let mut rects = vec![];
+            for _ in 0..2 {
+                run_ui(&ctx, |ui| {
+                    ui.allocate_ui(vec2(200.0, 200.0), |ui| {
+                        ui.horizontal_wrapped(|ui| {
+                            rects = (0..6)
+                                .map(|i| {
+                                    let header = CollapsingHeader::new(format!("Chip {i}"))
+                                        .chip_mode(chip_body)
+                                        .default_open(i == 1)
+                                        .show(ui, |ui| ui.label("Body"));
+                                    (header.header_response.rect, header.body_response)
+                                })
+                                .collect();
+                        });
+                    });
+                });
+            }
+
+            let (first, _) = &rects[0];
+            let (last, _) = &rects[5];
+            assert!(first.bottom() <= last.top(), "the chips should wrap");
+            let body = rects[1].1.as_ref().unwrap().rect;
+            assert!(rects[1].0.bottom() <= body.top());
+            match chip_body {
+                ChipBody::Popup => assert!(rects[2].0.top() < body.top()),
+                ChipBody::BelowRow => assert!(body.bottom() <= rects[2].0.top()),
+            }
+        }
+    }
+
+    #[test]
+    fn user_data_is_stored_with_header() {
+        #[derive(Clone, Debug, PartialEq)]
+        #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+        struct Note {
+            text: String,
+            stars: u8,
+        }
+
+        let ctx = Context::default();
+        let note = Note {
+            text: "Look at this later".to_owned(),
+            stars: 3,
+        };
+        let mut id = Id::NULL;
+        run_ui(&ctx, |ui| {
+            id = CollapsingHeader::new("Header")
+                .with_user_data(note.clone())
+                .show(ui, |_| {})
+                .header_response
+                .id;
+        });
+        run_ui(&ctx, |ui| {
+            CollapsingHeader::new("Header").show(ui, |_| {});
+        });
+        assert_eq!(CollapsingState::user_data::<Note>(&ctx, id), Some(note));
+        assert_eq!(CollapsingState::user_data::<String>(&ctx, id), None);
+    }
+
+    #[test]
+    fn body_enabled_leaves_header_live() {
+        fn show(ctx: &Context, events: Vec<Event>) -> (Rect, Option<bool>) {
+            let mut out = (Rect::NOTHING, None);
+            run_frame(ctx, events, |ui| {
+                let response = CollapsingHeader::new("Header")
+                    .body_enabled(false)
+                    .show(ui, |ui| ui.button("Edit").enabled());
+                out = (response.header_response.rect, response.body_returned);
+            });
+            out
+        }
+
+        let ctx = Context::default();
+        let (rect, body_enabled) = show(&ctx, vec![]);
+        assert_eq!(body_enabled, None);
+
+        click(rect.center(), |events| show(&ctx, events));
+        let (_, body_enabled) = show(&ctx, vec![]);
+        assert_eq!(body_enabled, Some(false));
+    }
+
+    #[test]
+    fn collapsed_galley_is_cached() {
+        let ctx = Context::default();
+        let mut galleys = vec![];
+        for open in [false, false, true, true] {
+            run_ui(&ctx, |ui| {
+                let response = CollapsingHeader::new("Header")
+                    .open(Some(open))
+                    .cache_collapsed_galley(true)
+                    .show(ui, |_| {});
+                let id = response.header_response.id.with("collapsed_galley");
+                galleys.push(ui.data(|d| d.get_temp::<(u64, Arc<Galley>)>(id)));
+            });
+        }
+        let (first, second) = (galleys[0].clone().unwrap(), galleys[1].clone().unwrap());
+        assert_eq!(first.0, second.0);
+        assert!(Arc::ptr_eq(&first.1, &second.1));
+        assert!(
+            galleys[3].is_none(),
+            "the cache should be dropped while open"
+        );
+    }
+
+    #[test]
+    fn icon_scale_range_scales_arrow() {
+        let arrow_size = |openness: f32| {
+            let ctx = Context::default();
+            let output = ctx.run(RawInput::default(), |ctx| {
+                CentralPanel::default().show(ctx, |ui| {
+                    let response = ui.allocate_response(vec2(16.0, 16.0), Sense::hover());
+                    paint_default_icon_scaled(ui, openness, &response, 0.5..=1.0);
+                });
+            });
+            let arrow = output
+                .shapes
+                .iter()
+                .find(|clipped| matches!(clipped.shape, Shape::Path(_)))
+                .unwrap();
+            arrow.shape.visual_bounding_rect().size()
+        };
+
+        let (closed, open) = (arrow_size(0.0), arrow_size(1.0));
+        assert!(
+            closed.x < open.x && closed.y < open.y,
+            "{closed:?} {open:?}"
+        );
+    }
+
+    #[test]
+    fn open_when_ready_waits_for_ready() {
+        let ctx = Context::default();
+        let show = |ready| {
+            let mut response = None;
+            run_ui(&ctx, |ui| {
+                let header = CollapsingHeader::new("Header")
+                    .open(Some(true))
+                    .open_when_ready(ready)
+                    .show(ui, |_| {});
+                response = Some((header.body_response.is_some(), header.openness));
+            });
+            response.unwrap()
+        };
+
+        for _ in 0..30 {
+            assert_eq!(show(false), (false, 0.0));
+        }
+        let (shown, openness) = show(true);
+        assert!(shown);
+        assert!(
+            0.0 < openness && openness < 1.0,
+            "should animate open once ready"
+        );
+    }
+
+    #[test]
+    fn resolve_id_matches_shown_id() {
+        let ctx = Context::default();
+        run_ui(&ctx, |ui| {
+            ui.push_id("scope", |ui| {
+                let header = CollapsingHeader::new("Header");
+                let id = header.resolve_id(ui);
+                assert_eq!(id, header.show(ui, |_| {}).header_response.id);
+
+                let chip = CollapsingHeader::new("Chip").chip_mode(ChipBody::Popup);
+                let id = chip.resolve_id(ui);
+                assert_eq!(id, chip.show(ui, |_| {}).header_response.id);
+            });
+        });
+    }
+
+    #[test]
+    fn rounded_body_clip_masks_corners_while_animating() {
+        let count_masks = |openness| {
+            let ctx = Context::default();
+            let output = ctx.run(RawInput::default(), |ctx| {
+                CentralPanel::default().show(ctx, |ui| {
+                    ui.visuals_mut().collapsing_header_frame = true;
+                    CollapsingHeader::new("Header")
+                        .default_open(true)
+                        .openness_override(openness)
+                        .rounded_body_clip(Color32::RED)
+                        .show(ui, |ui| ui.allocate_space(vec2(100.0, 100.0)));
+                });
+            });
+            output
+                .shapes
+                .iter()
+                .filter(|clipped| matches!(clipped.shape, Shape::Mesh(_)))
+                .count()
+        };
+
+        assert_eq!(count_masks(Some(0.5)), 2);
+        assert_eq!(count_masks(None), 0);
+    }
+
+    #[test]
+    fn non_collapsible_has_header_metrics() {
+        let ctx = Context::default();
+        run_ui(&ctx, |ui| {
+            ui.visuals_mut().collapsing_header_frame = true;
+            let header = CollapsingHeader::new("Header").show(ui, |_| {});
+            let label = CollapsingHeader::new("Label")
+                .non_collapsible(true)
+                .default_open(true)
+                .show(ui, |_| {});
+            assert_eq!(
+                label.header_response.rect.size(),
+                header.header_response.rect.size()
+            );
+            assert!(label.toggle_response.is_none());
+            assert!(label.body_response.is_none());
+        });
+    }
+
+    #[test]
+    fn click_toggles_false_only_reports_click() {
+        fn show(ctx: &Context, events: Vec<Event>) -> (Rect, bool, bool) {
+            let mut out = (Rect::NOTHING, false, false);
+            run_frame(ctx, events, |ui| {
+                let response = CollapsingHeader::new("Header")
+                    .click_toggles(false)
+                    .show(ui, |_| {});
+                out = (
+                    response.header_response.rect,
+                    response.header_response.clicked(),
+                    response.openness > 0.0,
+                );
+            });
+            out
+        }
+
+        let ctx = Context::default();
+        let (rect, _, _) = show(&ctx, vec![]);
+        let (_, clicked, open) = click(rect.center(), |events| show(&ctx, events));
+        assert!(clicked);
+        assert!(!open);
+        let (_, _, open) = show(&ctx, vec![]);
+        assert!(!open);
+    }
+
+    #[test]
+    fn peek_height_opens_in_two_stages() {
+        let ctx = Context::default();
+        let show = || {
+            let mut out = (Id::NULL, 0.0);
+            run_ui(&ctx, |ui| {
+                let response = CollapsingHeader::new("Header")
+                    .default_open(true)
+                    .peek_height(50.0)
+                    .show(ui, |ui| ui.allocate_space(vec2(10.0, 200.0)));
+                out = (
+                    response.header_response.id,
+                    response.body_response.unwrap().rect.height(),
+                );
+            });
+            out
+        };
+
+        let (id, _) = show();
+        let (_, peek) = show();
+        assert!(50.0 <= peek && peek < 200.0, "{peek}");
+
+        let mut state = CollapsingState::load(&ctx, id).unwrap();
+        state.set_fully_open(true);
+        state.store(&ctx);
+        let (_, animating) = show();
+        assert!(peek < animating && animating < 200.0, "{animating}");
+        for _ in 0..60 {
+            show();
+        }
+        let (_, full) = show();
+        assert!(200.0 <= full, "{full}");
+
+        // Closing goes back to peeking:
+        let mut state = CollapsingState::load(&ctx, id).unwrap();
+        state.set_open(false);
+        assert!(!state.is_fully_open());
+    }
+
+    #[test]
+    fn peek_more_text_reveals_the_body() {
+        let ctx = Context::default();
+        let show = |events: Vec<Event>| {
+            let input = RawInput {
+                events,
+                time: Some(ctx.input(|i| i.time) + 1.0 / 60.0),
+                ..Default::default()
+            };
+            let mut id = Id::NULL;
+            let output = ctx.run(input, |ctx| {
+                CentralPanel::default().show(ctx, |ui| {
+                    id = CollapsingHeader::new("Header")
+                        .default_open(true)
+                        .peek_height(50.0)
+                        .peek_more_text("Read on")
+                        .show(ui, |ui| ui.allocate_space(vec2(10.0, 200.0)))
+                        .header_response
+                        .id;
+                });
+            });
+            let link = output
+                .shapes
+                .iter()
+                .find_map(|clipped| match &clipped.shape {
+                    Shape::Text(text) if text.galley.text() == "Read on" => {
+                        Some(text.visual_bounding_rect().center())
+                    }
+                    _ => None,
+                });
+            (id, link)
+        };
+        show(vec![]);
+        let (id, link) = show(vec![]);
+        let pos = link.expect("the link should be shown while peeking");
+        click(pos, show);
+        assert!(CollapsingState::load(&ctx, id).unwrap().is_fully_open());
+    }
+
+    #[test]
+    fn toggle_response_reports_arrow_clicks() {
+        fn show(ctx: &Context, events: Vec<Event>) -> (Rect, bool, bool) {
+            let mut out = (Rect::NOTHING, false, false);
+            run_frame(ctx, events, |ui| {
+                let response = CollapsingHeader::new("Header").show(ui, |_| {});
+                let toggle = response.toggle_response.unwrap();
+                out = (
+                    toggle.rect,
+                    toggle.secondary_clicked(),
+                    response.header_response.secondary_clicked(),
+                );
+            });
+            out
+        }
+
+        let ctx = Context::default();
+        let (arrow_rect, _, _) = show(&ctx, vec![]);
+        let pos = arrow_rect.center();
+        let click = |pressed| Event::PointerButton {
+            pos,
+            button: PointerButton::Secondary,
+            pressed,
+            modifiers: Modifiers::NONE,
+        };
+        show(&ctx, vec![Event::PointerMoved(pos), click(true)]);
+        let (_, arrow_clicked, header_clicked) = show(&ctx, vec![click(false)]);
+        assert!(arrow_clicked);
+        assert!(!header_clicked);
+
+        run_ui(&ctx, |ui| {
+            let leaf = CollapsingHeader::new("Leaf").leaf(true).show(ui, |_| {});
+            assert_eq!(leaf.toggle_or_header_response().id, leaf.header_response.id);
+        });
+    }
+
+    #[test]
+    fn drop_target_opens_on_drag_hover() {
+        fn show(ctx: &Context, events: Vec<Event>) -> (Rect, Rect, bool, f32) {
+            let mut out = (Rect::NOTHING, Rect::NOTHING, false, 0.0);
+            run_frame(ctx, events, |ui| {
+                let source = ui.dnd_drag_source(Id::new("source"), 42_u32, |ui| {
+                    ui.label("Drag me");
+                });
+                let header = CollapsingHeader::new("Folder")
+                    .drop_target(true)
+                    .show(ui, |_| {});
+                out = (
+                    source.response.rect,
+                    header.header_response.rect,
+                    header.drop_hovered,
+                    header.openness,
+                );
+            });
+            out
+        }
+
+        let ctx = Context::default();
+        let (source, header, _, _) = show(&ctx, vec![]);
+        show(
+            &ctx,
+            vec![
+                Event::PointerMoved(source.center()),
+                Event::PointerButton {
+                    pos: source.center(),
+                    button: PointerButton::Primary,
+                    pressed: true,
+                    modifiers: Modifiers::NONE,
+                },
+            ],
+        );
+        show(
+            &ctx,
+            vec![Event::PointerMoved(source.center() + vec2(20.0, 0.0))],
+        );
+        let (_, _, drop_hovered, openness) = show(&ctx, vec![Event::PointerMoved(header.center())]);
+        assert!(drop_hovered);
+        assert_eq!(openness, 0.0, "should only open after a while");
+
+        for _ in 0..60 {
+            show(&ctx, vec![]);
+        }
+        let (_, _, drop_hovered, openness) = show(&ctx, vec![]);
+        assert!(drop_hovered);
+        assert!(0.0 < openness);
+    }
+
+    #[test]
+    fn reveal_style_slide_moves_body_up() {
+        for reveal_style in [RevealStyle::Clip, RevealStyle::Slide] {
+            let ctx = Context::default();
+            let mut offset = 0.0;
+            for openness in [None, Some(0.5)] {
+                run_ui(&ctx, |ui| {
+                    let response = CollapsingHeader::new("Header")
+                        .default_open(true)
+                        .openness_override(openness)
+                        .reveal_style(reveal_style)
+                        .show_unindented(ui, |ui| ui.allocate_space(vec2(10.0, 100.0)).1);
+                    let body_rect = response.body_response.unwrap().rect;
+                    assert_eq!(body_rect.height(), openness.map_or(100.0, |o| 100.0 * o));
+                    offset = response.body_returned.unwrap().top() - body_rect.top();
+                });
+            }
+            match reveal_style {
+                RevealStyle::Clip => assert_eq!(offset, 0.0),
+                RevealStyle::Slide => assert_eq!(offset, -50.0),
+            }
+        }
+    }
+
+    #[test]
+    fn reset_animation_snaps_openness() {
+        let ctx = Context::default();
+        let show = |open| {
+            let mut out = (Id::NULL, 0.0);
+            run_ui(&ctx, |ui| {
+                let response = CollapsingHeader::new("Header")
+                    .open(Some(open))
+                    .show(ui, |_| {});
+                out = (response.header_response.id, response.openness);
+            });
+            out
+        };
+
+        show(true);
+        let (id, openness) = show(false);
+        assert!(0.0 < openness, "should be animating");
+
+        CollapsingState::reset_animation(&ctx, id);
+        let (_, openness) = show(false);
+        assert_eq!(openness, 0.0);
+    }
+
+    #[test]
+    fn union_response_covers_header_and_body() {
+        let ctx = Context::default();
+        run_ui(&ctx, |ui| {
+            let open = CollapsingHeader::new("Open")
+                .default_open(true)
+                .show(ui, |ui| ui.label("Body"));
+            let body = open.body_response.as_ref().unwrap();
+            assert_eq!(
+                open.union_response().rect,
+                open.header_response.rect.union(body.rect)
+            );
+
+            let closed = CollapsingHeader::new("Closed").show(ui, |ui| ui.label("Body"));
+            assert_eq!(closed.union_response().rect, closed.header_response.rect);
+        });
+    }
+
+    #[test]
+    fn custom_header_replaces_title() {
+        let ctx = Context::default();
+        run_ui(&ctx, |ui| {
+            let contents_rect = Arc::new(Mutex::new(None));
+            let response = CollapsingHeader::new("Header")
+                .custom_header({
+                    let contents_rect = contents_rect.clone();
+                    move |ui, openness, arrow| {
+                        assert_eq!(openness, 0.0);
+                        assert!(arrow.sense.click);
+                        *contents_rect.lock() = Some(ui.label("Custom").rect);
+                    }
+                })
+                .show(ui, |_| {});
+            let header_rect = response.header_response.rect;
+            let contents_rect = contents_rect.lock().unwrap();
+            assert!(header_rect.contains_rect(contents_rect));
+            assert!(response.toggle_response.unwrap().rect.right() <= contents_rect.left());
+        });
+    }
+
+    #[test]
+    fn dot_for_leaf_shares_the_arrow_gutter() {
+        let ctx = Context::default();
+        let output = ctx.run(RawInput::default(), |ctx| {
+            CentralPanel::default().show(ctx, |ui| {
+                CollapsingHeader::new("Group")
+                    .icon_style(CollapsingIcon::DotForLeaf)
+                    .show(ui, |_| {});
+                CollapsingHeader::new("Leaf")
+                    .leaf(true)
+                    .icon_style(CollapsingIcon::DotForLeaf)
+                    .show(ui, |_| {});
+            });
+        });
+
+        let mut arrow = None;
+        let mut dot = None;
+        for clipped in &output.shapes {
+            match &clipped.shape {
+                Shape::Path(_) => arrow = Some(clipped.shape.visual_bounding_rect()),
+                Shape::Circle(circle) => dot = Some(circle.center),
+                _ => {}
+            }
+        }
+        let (arrow, dot) = (arrow.unwrap(), dot.unwrap());
+        assert!((arrow.center().x - dot.x).abs() < 0.5, "{arrow:?} {dot:?}");
+        assert!(arrow.bottom() < dot.y);
+    }
+
+    #[test]
+    fn after_body_gets_animated_rect() {
+        let ctx = Context::default();
+        let rects = Arc::new(Mutex::new(vec![]));
+        for openness in [None, Some(0.5), Some(0.0)] {
+            run_ui(&ctx, |ui| {
+                let rects = rects.clone();
+                CollapsingHeader::new("Header")
+                    .default_open(true)
+                    .openness_override(openness)
+                    .after_body(move |_, rect| rects.lock().push(rect))
+                    .show(ui, |ui| ui.allocate_space(vec2(10.0, 100.0)));
+            });
+        }
+        let rects = rects.lock();
+        assert_eq!(rects.len(), 2, "not called when closed");
+        assert_eq!(rects[0].height(), 100.0);
+        assert_eq!(rects[1].height(), 50.0);
+    }
+
+    #[test]
+    fn set_open_instant_skips_animation() {
+        let ctx = Context::default();
+        let show = || {
+            let mut out = (Id::NULL, 0.0);
+            run_ui(&ctx, |ui| {
+                let response = CollapsingHeader::new("Header").show(ui, |_| {});
+                out = (response.header_response.id, response.openness);
+            });
+            out
+        };
+
+        let (id, openness) = show();
+        assert_eq!(openness, 0.0);
+
+        let mut state = CollapsingState::load(&ctx, id).unwrap();
+        state.set_open_instant(true, &ctx);
+        state.store(&ctx);
+        assert_eq!(show().1, 1.0);
+    }
+
+    #[test]
+    fn full_content_height_ignores_max_body_height() {
+        let ctx = Context::default();
+        let mut response = None;
+        for _ in 0..3 {
+            run_ui(&ctx, |ui| {
+                response = Some(
+                    CollapsingHeader::new("Tall")
+                        .default_open(true)
+                        .max_body_height(100.0)
+                        .show(ui, |ui| {
+                            ui.allocate_space(vec2(50.0, 500.0));
+                        }),
+                );
+            });
+        }
+        let response = response.unwrap();
+        let body_height = response.body_response.unwrap().rect.height();
+        assert!(
+            body_height < 150.0,
+            "body should be capped, was {body_height}"
+        );
+        let full = response.full_content_height.unwrap();
+        assert!((full - 500.0).abs() < 1.0, "full height was {full}");
+    }
+
+    #[test]
+    fn height_capped_footer_shows_the_given_text() {
+        let ctx = Context::default();
+        let show = |events: Vec<Event>| {
+            let input = RawInput {
+                events,
+                time: Some(ctx.input(|i| i.time) + 1.0 / 60.0),
+                ..Default::default()
+            };
+            let mut body_height = 0.0;
+            let output = ctx.run(input, |ctx| {
+                CentralPanel::default().show(ctx, |ui| {
+                    let response = CollapsingHeader::new("Tall")
+                        .default_open(true)
+                        .max_body_height(100.0)
+                        .height_capped_footer(|hidden_height| {
+                            format!("{hidden_height:.0} hidden").into()
+                        })
+                        .show(ui, |ui| {
+                            ui.allocate_space(vec2(50.0, 500.0));
+                        });
+                    body_height = response.body_response.unwrap().rect.height();
+                });
+            });
+            let footer = output
+                .shapes
+                .iter()
+                .find_map(|clipped| match &clipped.shape {
+                    Shape::Text(text) if text.galley.text().ends_with(" hidden") => Some((
+                        text.galley.text().to_owned(),
+                        text.visual_bounding_rect().center(),
+                    )),
+                    _ => None,
+                });
+            (footer, body_height)
+        };
+        for _ in 0..3 {
+            show(vec![]);
+        }
+        let (footer, _) = show(vec![]);
+        let (text, pos) = footer.expect("the footer should be shown");
+        assert_eq!(text, "400 hidden");
+
+        click(pos, show);
+        let (footer, body_height) = show(vec![]);
+        assert!(footer.is_none(), "clicking the footer uncaps the body");
+        assert!(500.0 <= body_height, "body height was {body_height}");
+    }
+
+    #[test]
+    fn auto_expand_on_growth_opens_once() {
+        let ctx = Context::default();
+        let mut lines = 3;
+        let mut response = None;
+        let mut show = |ctx: &Context, lines: usize| {
+            run_ui(ctx, |ui| {
+                response = Some(
+                    CollapsingHeader::new("Log")
+                        .auto_expand_on_growth(true)
+                        .show(ui, |ui| {
+                            for i in 0..lines {
+                                ui.label(format!("line {i}"));
+                            }
+                        }),
+                );
+            });
+            response.take().unwrap()
+        };
+        let id = show(&ctx, lines).header_response.id;
+        assert!(!CollapsingState::load(&ctx, id).unwrap().is_open());
+
+        // Same content: stays closed.
+        show(&ctx, lines);
+        assert!(!CollapsingState::load(&ctx, id).unwrap().is_open());
+
+        // New content: opens.
+        lines += 1;
+        show(&ctx, lines);
+        let mut state = CollapsingState::load(&ctx, id).unwrap();
+        assert!(state.is_open());
+
+        // The user collapses it again; it stays closed until the next growth.
+        for _ in 0..2 {
+            show(&ctx, lines);
+        }
+        state.set_open(false);
+        state.store(&ctx);
+        for _ in 0..20 {
+            show(&ctx, lines);
+        }
+        assert!(!CollapsingState::load(&ctx, id).unwrap().is_open());
+
+        lines += 1;
+        show(&ctx, lines);
+        assert!(CollapsingState::load(&ctx, id).unwrap().is_open());
+    }
+
+    #[test]
+    fn show_region_for_header_and_window_state() {
+        let ctx = Context::default();
+        let mut bodies = vec![];
+        run_ui(&ctx, |ui| {
+            let header_id = ui.make_persistent_id("header");
+            let window_id = Id::new("window").with("collapsing");
+            for (id, open) in [(header_id, true), (window_id, false)] {
+                let state = CollapsingState::load_with_default_open(ui.ctx(), id, open);
+                let (header, body) = state.show_region(
+                    ui,
+                    |ui| {
+                        ui.label("Header");
+                    },
+                    |ui| ui.label("Body"),
+                );
+                assert!(header.rect.height() > 0.0);
+                bodies.push(body.is_some());
+            }
+        });
+        assert_eq!(bodies, vec![true, false]);
+    }
+
+    #[test]
+    fn image_icon_fits_icon_rect() {
+        let ctx = Context::default();
+        let mut icon_rect = Rect::NOTHING;
+        let output = ctx.run(RawInput::default(), |ctx| {
+            CentralPanel::default().show(ctx, |ui| {
+                let header = CollapsingHeader::new("Photos")
+                    .image_icon(TextureId::User(1), vec2(40.0, 20.0))
+                    .show(ui, |_| {});
+                let rect = header.header_response.rect;
+                let (mut icon, _) = ui.spacing().icon_rectangles(rect);
+                icon.set_center(pos2(
+                    rect.left() + ui.spacing().indent / 2.0,
+                    rect.center().y,
+                ));
+                icon_rect = icon;
+            });
+        });
+        let image = output
+            .shapes
+            .iter()
+            .find(|clipped| matches!(&clipped.shape, Shape::Mesh(mesh) if mesh.texture_id == TextureId::User(1)))
+            .unwrap();
+        let bounds = image.shape.visual_bounding_rect();
+        assert!(
+            icon_rect.expand(0.01).contains_rect(bounds),
+            "{bounds:?} {icon_rect:?}"
+        );
+        assert!(
+            (bounds.width() / bounds.height() - 2.0).abs() < 0.01,
+            "{bounds:?}"
+        );
+        assert_eq!(bounds.width(), icon_rect.width());
+    }
+
+    #[test]
+    fn debug_paints_diagnostics() {
+        let ctx = Context::default();
+        let debug_shapes = |debug| {
+            let output = ctx.run(RawInput::default(), |ctx| {
+                CentralPanel::default().show(ctx, |ui| {
+                    CollapsingHeader::new("Header")
+                        .default_open(true)
+                        .debug(debug)
+                        .show(ui, |ui| ui.label("Body"));
+                });
+            });
+            output.shapes.len()
+        };
+        assert!(debug_shapes(true) > debug_shapes(false));
+    }
+
+    #[test]
+    fn change_token_changes_on_toggle() {
+        let ctx = Context::default();
+        let token = |events: Vec<Event>| {
+            let mut token = None;
+            run_frame(&ctx, events, |ui| {
+                token = Some(
+                    CollapsingHeader::new("Header")
+                        .show(ui, |_| {})
+                        .change_token(),
+                );
+            });
+            token.unwrap()
+        };
+
+        let initial = token(vec![]);
+        assert_eq!(token(vec![]), initial);
+        let opened = click(pos2(20.0, 15.0), token);
+        assert_ne!(opened, initial);
+        for _ in 0..10 {
+            assert_eq!(token(vec![]), opened);
+        }
+        let closed = click(pos2(20.0, 15.0), token);
+        assert_ne!(closed, opened);
+        assert_eq!(token(vec![]), closed);
+    }
+
+    #[test]
+    fn text_align_right_puts_title_at_the_end() {
+        let ctx = Context::default();
+        let title_and_value = |value: Option<&str>| {
+            let mut header_rect = Rect::NOTHING;
+            let output = ctx.run(RawInput::default(), |ctx| {
+                CentralPanel::default().show(ctx, |ui| {
+                    let mut header = CollapsingHeader::new("Total").text_align(Align::RIGHT);
+                    if let Some(value) = value {
+                        header = header.value(value);
+                    }
+                    header_rect = header.show(ui, |_| {}).header_response.rect;
+                });
+            });
+            let mut texts: Vec<(String, Rect)> = output
+                .shapes
+                .iter()
+                .filter_map(|clipped| match &clipped.shape {
+                    Shape::Text(text) => Some((
+                        text.galley.text().to_owned(),
+                        clipped.shape.visual_bounding_rect(),
+                    )),
+                    _ => None,
+                })
+                .collect();
+            texts.sort_by(|a, b| a.0.cmp(&b.0));
+            (header_rect, texts)
+        };
+
+        let (header_rect, texts) = title_and_value(None);
+        let title = &texts[0].1;
+        let padding = ctx.style().spacing.button_padding.x;
+        assert!(
+            (title.right() - (header_rect.right() - padding)).abs() < 2.0,
+            "{title:?} {header_rect:?}"
+        );
+
+        let (header_rect, texts) = title_and_value(Some("42"));
+        let (value, title) = (&texts[0].1, &texts[1].1);
+        let spacing = ctx.style().spacing.item_spacing.x;
+        assert!(
+            (value.right() - (header_rect.right() - padding)).abs() < 2.0,
+            "{value:?} {header_rect:?}"
+        );
+        assert!(
+            (title.right() - (value.left() - spacing)).abs() < 2.0,
+            "{title:?} {value:?}"
+        );
+    }
+
+    #[test]
+    fn measure_expanded_measures_without_opening() {
+        let ctx = Context::default();
+        let mut ids = None;
+        let show_tree = |ui: &mut Ui| {
+            let outer = CollapsingHeader::new("Outer").show(ui, |ui| {
+                let inner = CollapsingHeader::new("Inner").show(ui, |ui| {
+                    ui.allocate_space(vec2(10.0, 100.0));
+                });
+                inner.header_response.id
+            });
+            (outer.header_response.id, outer.body_returned)
+        };
+
+        run_ui(&ctx, |ui| ids = Some(show_tree(ui)));
+        let (outer, inner) = ids.unwrap();
+        assert!(inner.is_none());
+        assert_eq!(
+            CollapsingState::load(&ctx, outer)
+                .unwrap()
+                .full_content_height(),
+            None
+        );
+
+        let mut measured_inner = None;
+        run_ui(&ctx, |ui| {
+            let ctx = ui.ctx().clone();
+            ui.set_invisible();
+            CollapsingState::measure_expanded(&ctx, outer, |_| {
+                measured_inner = show_tree(ui).1;
+            });
+        });
+        let inner = measured_inner.unwrap();
+        let outer_state = CollapsingState::load(&ctx, outer).unwrap();
+        let inner_state = CollapsingState::load(&ctx, inner).unwrap();
+        assert!(!outer_state.is_open() && !inner_state.is_open());
+        assert!(outer_state.full_content_height().unwrap() > 100.0);
+        assert_eq!(inner_state.full_content_height(), Some(100.0));
+
+        // Back to normal:
+        run_ui(&ctx, |ui| ids = Some(show_tree(ui)));
+        assert!(ids.unwrap().1.is_none());
+    }
+
+    #[test]
+    fn button_style_header_is_a_toggling_button() {
+        let ctx = Context::default();
+        let show = |events: Vec<Event>| {
+            let mut response = None;
+            run_frame(&ctx, events, |ui| {
+                let header = CollapsingHeader::new("Details")
+                    .button_style(true)
+                    .show(ui, |ui| ui.label("More"));
+                response = Some((header.header_response, header.body_response.is_some()));
+            });
+            response.unwrap()
+        };
+        let (header, open) = show(vec![]);
+        assert!(!open);
+        assert!(header.sense.click);
+
+        let (header, _) = click(header.rect.center(), show);
+        assert!(header.clicked());
+        let (_, open) = show(vec![]);
+        assert!(open);
+    }
+
+    #[test]
+    fn animation_remaining_counts_down() {
+        let ctx = Context::default();
+        let show = || {
+            let mut id = Id::NULL;
+            run_ui(&ctx, |ui| {
+                id = CollapsingHeader::new("Header")
+                    .show(ui, |ui| ui.label("Body"))
+                    .header_response
+                    .id;
+            });
+            id
+        };
+        let id = show();
+        assert_eq!(
+            CollapsingState::load(&ctx, id)
+                .unwrap()
+                .animation_remaining(&ctx),
+            0.0
+        );
+
+        let mut state = CollapsingState::load(&ctx, id).unwrap();
+        state.set_open(true);
+        state.store(&ctx);
+        show();
+        show();
+
+        let animation_time = ctx.style().animation_time;
+        let dt = ctx.input(|i| i.stable_dt);
+        let remaining = CollapsingState::load(&ctx, id)
+            .unwrap()
+            .animation_remaining(&ctx);
+        let expected = animation_time - 2.0 * dt;
+        assert!(
+            (remaining - expected).abs() <= dt,
+            "remaining {remaining}, expected about {expected}"
+        );
+
+        for _ in 0..30 {
+            show();
+        }
+        assert_eq!(
+            CollapsingState::load(&ctx, id)
+                .unwrap()
+                .animation_remaining(&ctx),
+            0.0
+        );
+    }
+
+    #[test]
+    fn card_encloses_header_and_body() {
+        let ctx = Context::default();
+        let fill = Color32::from_rgb(1, 2, 3);
+        for _ in 0..3 {
+            let mut rects = None;
+            let output = ctx.run(RawInput::default(), |ctx| {
+                CentralPanel::default().show(ctx, |ui| {
+                    let response = CollapsingHeader::new("Card")
+                        .default_open(true)
+                        .card(Frame::group(ui.style()).fill(fill))
+                        .show(ui, |ui| ui.label("Body"));
+                    rects = Some((
+                        response.header_response.rect,
+                        response.body_response.unwrap().rect,
+                    ));
+                });
+            });
+            let (header, body) = rects.unwrap();
+            let frame = output
+                .shapes
+                .iter()
+                .find_map(|clipped| match &clipped.shape {
+                    Shape::Rect(rect) if rect.fill == fill => Some(rect.rect),
+                    _ => None,
+                })
+                .unwrap();
+            assert!(
+                frame.contains_rect(header.union(body)),
+                "{frame:?} {header:?} {body:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn manage_state_off_stores_nothing() {
+        let ctx = Context::default();
+        let mut open = false;
+        let mut id = Id::NULL;
+        for frame in 0..20 {
+            if frame == 5 {
+                open = true;
+            }
+            let mut body_shown = false;
+            run_ui(&ctx, |ui| {
+                let response = CollapsingHeader::new("External")
+                    .manage_state(false)
+                    .open(Some(open))
+                    .estimated_body_height(50.0)
+                    .show(ui, |ui| ui.label("Body"));
+                id = response.header_response.id;
+                body_shown = response.body_response.is_some();
+            });
+            assert_eq!(body_shown, open);
+            assert!(ctx
+                .data_mut(|d| d.get_persisted::<InnerState>(id))
+                .is_none());
+        }
+    }
+
+    #[test]
+    fn flat_nesting_keeps_deep_content_wide() {
+        fn nest(ui: &mut Ui, depth: usize, flat: bool, widths: &mut Vec<f32>) {
+            widths.push(ui.available_width());
+            if depth < 5 {
+                CollapsingHeader::new(format!("Level {depth}"))
+                    .default_open(true)
+                    .flat_nesting(flat)
+                    .show(ui, |ui| nest(ui, depth + 1, flat, widths));
+            }
+        }
+
+        let widths = |flat| {
+            let ctx = Context::default();
+            let mut widths = vec![];
+            run_ui(&ctx, |ui| {
+                widths.clear();
+                nest(ui, 0, flat, &mut widths);
+            });
+            widths
+        };
+
+        let flat = widths(true);
+        assert_eq!(flat.len(), 6);
+        let shrink = flat[1] - flat[5];
+        assert!(
+            shrink <= 4.0 * CollapsingHeader::FLAT_NESTING_INDENT + 0.5,
+            "{flat:?}"
+        );
+
+        let indented = widths(false);
+        assert!(indented[1] - indented[5] > 2.0 * shrink, "{indented:?}");
+    }
+
+    #[test]
+    fn body_background_clicked_ignores_child_clicks() {
+        let ctx = Context::default();
+        let show = |events: Vec<Event>| {
+            let mut result = None;
+            run_frame(&ctx, events, |ui| {
+                let mut button = None;
+                let response = CollapsingHeader::new("Items")
+                    .default_open(true)
+                    .sense_body_background(true)
+                    .show(ui, |ui| {
+                        button = Some(ui.button("Item"));
+                        ui.allocate_space(vec2(100.0, 100.0));
+                    });
+                let button = button.unwrap();
+                let body = response.body_response.as_ref().unwrap().rect;
+                result = Some((
+                    response.body_background_clicked(),
+                    button.clicked(),
+                    button.rect,
+                    body,
+                ));
+            });
+            result.unwrap()
+        };
+        let click = |pos| click(pos, show);
+
+        let (_, _, button, body) = show(vec![]);
+        let (background_clicked, button_clicked, _, _) = click(button.center());
+        assert!(button_clicked && !background_clicked);
+
+        let (background_clicked, button_clicked, _, _) =
+            click(pos2(body.center().x, body.bottom() - 10.0));
+        assert!(background_clicked && !button_clicked);
+    }
+
+    #[test]
+    fn min_animate_height_snaps_short_bodies() {
+        let openness_after_closing = |body_height: f32| {
+            let ctx = Context::default();
+            let show = || {
+                let mut response = None;
+                run_ui(&ctx, |ui| {
+                    let header = CollapsingHeader::new("Header")
+                        .default_open(true)
+                        .min_animate_height(10.0)
+                        .show(ui, |ui| ui.allocate_space(vec2(10.0, body_height)));
+                    response = Some((header.header_response.id, header.openness));
+                });
+                response.unwrap()
+            };
+            let (id, _) = show();
+            let mut state = CollapsingState::load(&ctx, id).unwrap();
+            state.set_open(false);
+            state.store(&ctx);
+            show().1
+        };
+        assert_eq!(openness_after_closing(5.0), 0.0);
+        let openness = openness_after_closing(50.0);
+        assert!(0.0 < openness && openness < 1.0, "{openness}");
+    }
+
+    #[test]
+    fn display_queue_applies_one_event_per_frame() {
+        let ctx = Context::default();
+        let queue = Arc::new(Mutex::new(VecDeque::from([
+            CollapsingEvent::Expand,
+            CollapsingEvent::Collapse,
+        ])));
+        let show = || {
+            let mut response = None;
+            run_ui(&ctx, |ui| {
+                let header = CollapsingHeader::new("Tour")
+                    .display_queue(queue.clone())
+                    .show(ui, |_| {});
+                response = Some((header.header_response.changed(), header.header_response.id));
+            });
+            let (changed, id) = response.unwrap();
+            (changed, CollapsingState::load(&ctx, id).unwrap().is_open())
+        };
+
+        assert_eq!(show(), (true, true));
+        assert_eq!(queue.lock().len(), 1);
+        assert_eq!(show(), (true, false));
+        assert!(queue.lock().is_empty());
+        assert_eq!(show(), (false, false));
+    }
+
+    #[test]
+    fn display_queue_reports_applied_event() {
+        let ctx = Context::default();
+        let queue = Arc::new(Mutex::new(VecDeque::from([
+            CollapsingEvent::Toggle,
+            CollapsingEvent::Expand,
+            CollapsingEvent::Toggle,
+        ])));
+        let mut applied = vec![];
+        for _ in 0..4 {
+            run_ui(&ctx, |ui| {
+                let response = CollapsingHeader::new("Menu")
+                    .display_queue(queue.clone())
+                    .show(ui, |_| {});
+                applied.push(response.applied_event);
+            });
+        }
+        assert_eq!(
+            applied,
+            [
+                Some((CollapsingEvent::Toggle, true)),
+                Some((CollapsingEvent::Expand, true)),
+                Some((CollapsingEvent::Toggle, false)),
+                None,
+            ]
+        );
+    }
+
+    #[test]
+    fn modified_paints_a_dot() {
+        let dots = |modified| {
+            let ctx = Context::default();
+            let output = ctx.run(RawInput::default(), |ctx| {
+                CentralPanel::default().show(ctx, |ui| {
+                    CollapsingHeader::new("Document")
+                        .modified(modified)
+                        .show(ui, |_| {});
+                });
+            });
+            let color = ctx.style().visuals.warn_fg_color;
+            output
+                .shapes
+                .iter()
+                .filter(|clipped| matches!(&clipped.shape, Shape::Circle(circle) if circle.fill == color))
+                .count()
+        };
+        assert_eq!(dots(false), 0);
+        assert_eq!(dots(true), 1);
+    }
+
+    #[test]
+    fn cached_galley_follows_pixels_per_point() {
+        let ctx = Context::default();
+        let show = |cached| {
+            let mut header = None;
+            run_ui(&ctx, |ui| {
+                let response = CollapsingHeader::new("Scaled")
+                    .id_source(cached)
+                    .cache_collapsed_galley(cached)
+                    .show(ui, |_| {});
+                header = Some(response.header_response);
+            });
+            header.unwrap()
+        };
+        let cached_ppp = |header: &Response| {
+            let id = header.id.with("collapsed_galley");
+            ctx.data(|d| d.get_temp::<(u64, Arc<Galley>)>(id))
+                .unwrap()
+                .1
+                .pixels_per_point
+        };
+
+        for pixels_per_point in [1.0, 2.0, 1.5] {
+            ctx.set_pixels_per_point(pixels_per_point);
+            show(true);
+            let (cached, plain) = (show(true), show(false));
+            assert_eq!(cached_ppp(&cached), pixels_per_point);
+            assert_eq!(cached.rect.size(), plain.rect.size());
+        }
+    }
+
+    #[test]
+    fn cached_galley_follows_the_title_style() {
+        let ctx = Context::default();
+        let show = |color: Color32, text_style: Option<TextStyle>| {
+            let mut galley = None;
+            run_ui(&ctx, |ui| {
+                ui.style_mut().override_text_style = text_style.clone();
+                let response = CollapsingHeader::new(RichText::new("Styled").color(color))
+                    .cache_collapsed_galley(true)
+                    .show(ui, |_| {});
+                let id = response.header_response.id.with("collapsed_galley");
+                galley = ui.data(|d| d.get_temp::<(u64, Arc<Galley>)>(id));
+            });
+            galley.unwrap().1
+        };
+        let section = |galley: &Galley| galley.job.sections[0].format.clone();
+
+        show(Color32::RED, None);
+        let red = show(Color32::RED, None);
+        assert!(Arc::ptr_eq(&red, &show(Color32::RED, None)));
+
+        let green = show(Color32::GREEN, None);
+        assert_eq!(section(&green).color, Color32::GREEN);
+
+        let monospace = show(Color32::GREEN, Some(TextStyle::Monospace));
+        assert_eq!(
+            section(&monospace).font_id,
+            TextStyle::Monospace.resolve(&ctx.style())
+        );
+    }
+
+    #[test]
+    fn body_busy_blocks_interaction() {
+        let clicked = |busy| {
+            let ctx = Context::default();
+            let show = |events: Vec<Event>| {
+                let mut button = None;
+                run_frame(&ctx, events, |ui| {
+                    CollapsingHeader::new("Reloading")
+                        .default_open(true)
+                        .body_busy(busy)
+                        .show(ui, |ui| button = Some(ui.button("Item")));
+                });
+                button.unwrap()
+            };
+            let pos = show(vec![]).rect.center();
+            click(pos, show).clicked()
+        };
+        assert!(clicked(false));
+        assert!(!clicked(true));
+    }
+
+    #[test]
+    fn collapse_all_leaves_pinned_open() {
+        let ctx = Context::default();
+        let show = |events: Vec<Event>| {
+            let mut headers = vec![];
+            run_frame(&ctx, events, |ui| {
+                for name in ["A", "B", "C"] {
+                    let response = CollapsingHeader::new(name)
+                        .default_open(true)
+                        .pinnable(true)
+                        .show(ui, |ui| ui.label("Body"));
+                    headers.push(response.header_response);
+                }
+            });
+            headers
+        };
+        let is_open = |header: &Response| CollapsingState::load(&ctx, header.id).unwrap().is_open();
+
+        // Click the pin of B:
+        let headers = show(vec![]);
+        let b = &headers[1];
+        let pin = pos2(b.rect.right() - 10.0, b.rect.center().y);
+        let headers = click(pin, show);
+        assert!(CollapsingState::load(&ctx, headers[1].id)
+            .unwrap()
+            .is_pinned());
+        assert!(
+            headers.iter().all(is_open),
+            "clicking the pin should not toggle the header"
+        );
+
+        CollapsingState::collapse_all(&ctx);
+        let headers = show(vec![]);
+        let open: Vec<bool> = headers.iter().map(is_open).collect();
+        assert_eq!(open, vec![false, true, false]);
+        assert!(CollapsingState::load(&ctx, headers[1].id)
+            .unwrap()
+            .is_pinned());
+    }
+
+    #[test]
+    fn fade_edge_only_while_animating() {
+        let ctx = Context::default();
+        let has_fade = |ctx: &Context| {
+            let mut id = Id::NULL;
+            let output = ctx.run(
+                RawInput {
+                    time: Some(ctx.input(|i| i.time) + 1.0 / 60.0),
+                    ..Default::default()
+                },
+                |ctx| {
+                    CentralPanel::default().show(ctx, |ui| {
+                        id = CollapsingHeader::new("Header")
+                            .fade_edge(20.0)
+                            .show(ui, |ui| ui.allocate_space(vec2(50.0, 200.0)))
+                            .header_response
+                            .id;
+                    });
+                },
+            );
+            let fade = output.shapes.iter().any(|clipped| {
+                matches!(&clipped.shape, Shape::Mesh(mesh)
+                    if mesh.vertices.iter().any(|v| v.color == Color32::TRANSPARENT))
+            });
+            (id, fade)
+        };
+
+        let (id, fade) = has_fade(&ctx);
+        assert!(!fade);
+        let mut state = CollapsingState::load(&ctx, id).unwrap();
+        state.set_open(true);
+        state.store(&ctx);
+        has_fade(&ctx); // measure the height
+        assert!(has_fade(&ctx).1, "fades while opening");
+        for _ in 0..30 {
+            has_fade(&ctx);
+        }
+        assert!(!has_fade(&ctx).1, "no fade when open");
+    }
+
+    #[test]
+    fn count_open_counts_open_sections() {
+        let ctx = Context::default();
+        let show = || {
+            let mut ids = vec![];
+            run_ui(&ctx, |ui| {
+                for name in ["A", "B", "C"] {
+                    let response = CollapsingHeader::new(name).show(ui, |ui| {
+                        CollapsingHeader::new("Nested").show(ui, |_| {});
+                    });
+                    ids.push(response.header_response.id);
+                }
+            });
+            ids
+        };
+        let ids = show();
+        assert_eq!(CollapsingState::count_open(&ctx, Id::NULL), 0);
+
+        for &id in &ids[..2] {
+            let mut state = CollapsingState::load(&ctx, id).unwrap();
+            state.set_open(true);
+            state.store(&ctx);
+        }
+        show();
+        assert_eq!(CollapsingState::count_open(&ctx, Id::NULL), 2);
+        assert_eq!(CollapsingState::count_open(&ctx, ids[0]), 0);
+    }
+
+    #[test]
+    fn overshoot_settles_at_open() {
+        let ctx = Context::default();
+        let show = || {
+            let mut result = None;
+            run_ui(&ctx, |ui| {
+                let response = CollapsingHeader::new("Bouncy")
+                    .animation(AnimationStyle::Overshoot { amount: 1.7 })
+                    .show(ui, |ui| ui.allocate_space(vec2(50.0, 100.0)));
+                let body_height = response.body_response.as_ref().map(|r| r.rect.height());
+                result = Some((response.header_response.id, response.openness, body_height));
+            });
+            result.unwrap()
+        };
+        let (id, _, _) = show();
+        let mut state = CollapsingState::load(&ctx, id).unwrap();
+        state.set_open(true);
+        state.store(&ctx);
+
+        let mut max_openness = 0.0_f32;
+        for _ in 0..30 {
+            let (_, openness, body_height) = show();
+            max_openness = max_openness.max(openness);
+            if let Some(body_height) = body_height {
+                assert!(body_height <= 100.0 + 0.01);
             }
+        }
+        assert!(
+            max_openness > 1.0,
+            "should overshoot, max was {max_openness}"
+        );
+        assert_eq!(show().1, 1.0);
+    }
 
-            let Prepared {
-                header_response,
-                mut state,
-                openness,
-            } = self.begin(ui); // show the header
+    #[test]
+    fn overshoot_reverses_smoothly() {
+        let ctx = Context::default();
+        let show = |open: bool| {
+            let mut openness = 0.0;
+            run_ui(&ctx, |ui| {
+                openness = CollapsingHeader::new("Bouncy")
+                    .open(Some(open))
+                    .animation(AnimationStyle::Overshoot { amount: 1.7 })
+                    .animation_time(1.0)
+                    .show(ui, |ui| ui.allocate_space(vec2(50.0, 100.0)))
+                    .openness;
+            });
+            openness
+        };
+        let mut openness = vec![show(false)];
+        // Half way open, then close again:
+        for frame in 0..120 {
+            openness.push(show(frame < 30));
+        }
+        assert!(openness[30] > 1.0, "overshooting when reversed");
+        let max_step = openness
+            .windows(2)
+            .map(|pair| (pair[1] - pair[0]).abs())
+            .fold(0.0, f32::max);
+        assert!(max_step < 0.15, "{openness:?}");
+        assert_eq!(openness.last(), Some(&0.0));
 
-            let ret_response = if indented {
-                state.show_body_indented(&header_response, ui, add_body)
-            } else {
-                state.show_body_unindented(ui, add_body)
+        // Closing without a reversal is the same as `Responsive`:
+        let t = 0.3;
+        let ctx = Context::default();
+        let id = Id::new("segment");
+        let overshoot = AnimationStyle::Overshoot { amount: 1.7 };
+        overshoot.ease(&ctx, id, true, 1.0);
+        let closing = overshoot.ease(&ctx, id, false, t);
+        let responsive = 1.0 - emath::easing::cubic_out(1.0 - t);
+        assert!((closing - responsive).abs() < 1e-6);
+    }
+
+    #[test]
+    fn chevron_swap_points_differ_when_open() {
+        let chevron_points = |openness: f32| {
+            let ctx = Context::default();
+            let output = ctx.run(RawInput::default(), |ctx| {
+                CentralPanel::default().show(ctx, |ui| {
+                    let response = ui.allocate_response(vec2(16.0, 16.0), Sense::hover());
+                    paint_chevron_swap_icon(ui, openness, &response);
+                });
+            });
+            let paths: Vec<_> = output
+                .shapes
+                .iter()
+                .filter_map(|clipped| match &clipped.shape {
+                    Shape::Path(path) => Some(path.points.clone()),
+                    _ => None,
+                })
+                .collect();
+            assert_eq!(paths.len(), 1, "only one chevron is visible at the ends");
+            paths[0].clone()
+        };
+
+        let (closed, open) = (chevron_points(0.0), chevron_points(1.0));
+        assert_ne!(closed, open);
+        // Down when closed, up when open:
+        assert!(closed[1].y > closed[0].y);
+        assert!(open[1].y < open[0].y);
+    }
+
+    #[test]
+    fn cache_body_replays_until_invalidated() {
+        let ctx = Context::default();
+        let calls = Cell::new(0);
+        let show = || {
+            let mut result = None;
+            let output = ctx.run(RawInput::default(), |ctx| {
+                CentralPanel::default().show(ctx, |ui| {
+                    let response = CollapsingHeader::new("Document")
+                        .default_open(true)
+                        .cache_body(true)
+                        .show(ui, |ui| {
+                            calls.set(calls.get() + 1);
+                            ui.label("A long static text");
+                        });
+                    result = Some((
+                        response.header_response.id,
+                        response.body_response.unwrap().rect,
+                    ));
+                });
+            });
+            let texts = output
+                .shapes
+                .iter()
+                .filter(|clipped| matches!(clipped.shape, Shape::Text(_)))
+                .count();
+            let (id, body_rect) = result.unwrap();
+            (id, body_rect, texts)
+        };
+
+        let (id, body_rect, texts) = show();
+        assert_eq!(calls.get(), 1);
+        for _ in 0..3 {
+            assert_eq!(show(), (id, body_rect, texts), "the body is replayed");
+        }
+        assert_eq!(calls.get(), 1);
+
+        CollapsingHeader::invalidate_body(&ctx, id);
+        show();
+        assert_eq!(calls.get(), 2);
+
+        // The recorded text was laid out for another scale or style:
+        ctx.set_pixels_per_point(2.0);
+        show();
+        assert_eq!(calls.get(), 3);
+        show();
+        assert_eq!(calls.get(), 3);
+        ctx.set_visuals(Visuals::light());
+        show();
+        assert_eq!(calls.get(), 4);
+    }
+
+    #[test]
+    fn cache_body_records_only_unclipped_bodies() {
+        // Count the labels that end up on screen, scrolled `offset` down a short scroll area:
+        let visible_texts = |ctx: &Context, cache_body: bool, offset: f32| {
+            let output = ctx.run(RawInput::default(), |ctx| {
+                CentralPanel::default().show(ctx, |ui| {
+                    ScrollArea::vertical()
+                        .max_height(100.0)
+                        .vertical_scroll_offset(offset)
+                        .show(ui, |ui| {
+                            CollapsingHeader::new("Document")
+                                .default_open(true)
+                                .cache_body(cache_body)
+                                .show(ui, |ui| {
+                                    for i in 0..30 {
+                                        ui.label(format!("Line {i}"));
+                                    }
+                                });
+                        });
+                });
+            });
+            output
+                .shapes
+                .iter()
+                .filter(|clipped| {
+                    matches!(clipped.shape, Shape::Text(_))
+                        && clipped
+                            .clip_rect
+                            .intersects(clipped.shape.visual_bounding_rect())
+                })
+                .count()
+        };
+
+        let (cached, uncached) = (Context::default(), Context::default());
+        // The first frame of a scroll area isn't clipped yet:
+        visible_texts(&cached, false, 0.0);
+        visible_texts(&uncached, false, 0.0);
+        for offset in [0.0, 0.0, 200.0, 200.0, 400.0] {
+            assert_eq!(
+                visible_texts(&cached, true, offset),
+                visible_texts(&uncached, false, offset),
+                "scrolled to {offset}"
+            );
+        }
+    }
+
+    #[test]
+    fn tab_focuses_headers() {
+        let ctx = Context::default();
+        let tab = Event::Key {
+            key: Key::Tab,
+            physical_key: None,
+            pressed: true,
+            repeat: false,
+            modifiers: Modifiers::NONE,
+        };
+        let show = |events: Vec<Event>| {
+            let mut focused = vec![];
+            run_frame(&ctx, events, |ui| {
+                focused = ["First", "Second"]
+                    .map(|title| {
+                        CollapsingHeader::new(title)
+                            .show(ui, |ui| ui.label("Body"))
+                            .header_has_focus()
+                    })
+                    .to_vec();
+            });
+            focused
+        };
+
+        assert_eq!(show(vec![]), [false, false]);
+        show(vec![tab.clone()]);
+        assert_eq!(show(vec![]), [true, false]);
+        show(vec![tab]);
+        assert_eq!(show(vec![]), [false, true]);
+    }
+
+    #[test]
+    fn collapse_if_space_below_keeps_open_state() {
+        let ctx = Context::default();
+        let show = |height: f32| {
+            let mut result = None;
+            run_ui(&ctx, |ui| {
+                let rect = Rect::from_min_size(ui.cursor().min, vec2(200.0, height));
+                let mut panel_ui = ui.child_ui(rect, Layout::top_down(Align::Min), None);
+                let response = CollapsingHeader::new("Statistics")
+                    .default_open(true)
+                    .collapse_if_space_below(100.0)
+                    .show(&mut panel_ui, |ui| ui.label("Body"));
+                result = Some((
+                    response.header_response.id,
+                    response.body_response.is_some(),
+                ));
+            });
+            result.unwrap()
+        };
+
+        let (id, shown) = show(50.0);
+        assert!(!shown, "there is no room for the body");
+        assert!(CollapsingState::load(&ctx, id).unwrap().is_open());
+
+        assert_eq!(show(500.0), (id, true), "shown again once there is room");
+    }
+
+    #[test]
+    fn open_bitmask_round_trip() {
+        let ids: Vec<Id> = (0..64).map(|i| Id::new(("node", i))).collect();
+        let is_open = |i: usize| i % 3 == 0 || i == 63;
+
+        let ctx = Context::default();
+        for (i, &id) in ids.iter().enumerate() {
+            CollapsingState::load_with_default_open(&ctx, id, is_open(i)).store(&ctx);
+        }
+        let bits = CollapsingState::open_bitmask(&ctx, &ids);
+        assert_eq!(bits.len(), 1);
+
+        let restored = Context::default();
+        CollapsingState::apply_open_bitmask(&restored, &ids, &bits);
+        for (i, &id) in ids.iter().enumerate() {
+            let state = CollapsingState::load(&restored, id).unwrap();
+            assert_eq!(state.is_open(), is_open(i), "node {i}");
+        }
+    }
+
+    #[test]
+    fn right_click_opens_context_menu_without_toggling() {
+        let ctx = Context::default();
+        let show = |events: Vec<Event>| {
+            let mut result = None;
+            let menu_shown = Arc::new(Mutex::new(false));
+            run_frame(&ctx, events, |ui| {
+                let menu_shown = menu_shown.clone();
+                let response = CollapsingHeader::new("Node")
+                    .context_menu(move |ui| {
+                        *menu_shown.lock() = true;
+                        ui.label("Rename");
+                    })
+                    .show(ui, |ui| ui.label("Body"));
+                result = Some((
+                    response.header_response.rect,
+                    response.body_response.is_some(),
+                    response.context_menu_opened(),
+                ));
+            });
+            let (rect, open, menu_opened) = result.unwrap();
+            let menu_shown = *menu_shown.lock();
+            (rect, open, menu_opened, menu_shown)
+        };
+
+        let (rect, open, menu_opened, _) = show(vec![]);
+        assert!(!open && !menu_opened);
+
+        let pos = rect.center();
+        let click = |pressed| Event::PointerButton {
+            pos,
+            button: PointerButton::Secondary,
+            pressed,
+            modifiers: Modifiers::NONE,
+        };
+        show(vec![Event::PointerMoved(pos), click(true)]);
+        show(vec![click(false)]);
+        let (_, open, menu_opened, menu_shown) = show(vec![]);
+        assert!(menu_opened && menu_shown);
+        assert!(!open, "right-clicking shouldn't toggle the header");
+    }
+
+    #[test]
+    fn show_editable_writes_back_the_new_title() {
+        let ctx = Context::default();
+        let title = std::cell::RefCell::new(String::from("Folder"));
+        let show = |events: Vec<Event>| {
+            let mut result = None;
+            run_frame(&ctx, events, |ui| {
+                let text = title.borrow().clone();
+                let response = CollapsingHeader::new(text)
+                    .id_source("folder")
+                    .show_editable(ui, &mut title.borrow_mut(), |ui| ui.label("Body"));
+                result = Some((
+                    response.header_response.rect,
+                    response.body_response.is_some(),
+                    response.renamed_title,
+                ));
+            });
+            result.unwrap()
+        };
+
+        let (rect, _, _) = show(vec![]);
+        let pos = rect.right_center() - vec2(10.0, 0.0); // on the title, not the arrow
+        click(pos, show);
+        click(pos, show);
+        show(vec![Event::Text("s".to_owned())]);
+        let enter = Event::Key {
+            key: Key::Enter,
+            physical_key: None,
+            pressed: true,
+            repeat: false,
+            modifiers: Modifiers::NONE,
+        };
+        let (_, open, renamed_title) = show(vec![enter]);
+        assert_eq!(renamed_title.as_deref(), Some("Folders"));
+        assert_eq!(*title.borrow(), "Folders");
+        assert!(
+            !open,
+            "double-clicking the title shouldn't toggle the header"
+        );
+        assert_eq!(show(vec![]).2, None, "only returned once");
+    }
+
+    #[test]
+    fn header_height_matches_shown_header() {
+        let ctx = Context::default();
+        run_ui(&ctx, |ui| {
+            let headers = [
+                || CollapsingHeader::new("Plain"),
+                || CollapsingHeader::new("Compact").compact(true),
+                || CollapsingHeader::new("Two\nlines"),
+                || CollapsingHeader::new(RichText::new("Big").heading()),
+            ];
+            for header in headers {
+                let height = header().header_height(ui);
+                let response = header().show(ui, |_| {});
+                assert_eq!(height, response.header_response.rect.height());
+            }
+        });
+    }
+
+    #[test]
+    fn header_body_gap_offsets_body() {
+        let ctx = Context::default();
+        for gap in [20.0_f32, -2.0] {
+            run_ui(&ctx, |ui| {
+                let response = CollapsingHeader::new("Card")
+                    .id_source(gap.to_bits())
+                    .open(Some(true))
+                    .openness_override(Some(1.0))
+                    .header_body_gap(gap)
+                    .show(ui, |ui| ui.label("Body"));
+                let body_top = response.body_response.unwrap().rect.top();
+                let header_bottom = response.header_response.rect.bottom();
+                assert!((body_top - header_bottom - gap).abs() < 0.01, "gap {gap}");
+            });
+        }
+    }
+
+    #[test]
+    fn show_header_animated_gets_openness() {
+        let ctx = Context::default();
+        let id = Id::new("animated_header");
+        let mut seen = vec![];
+        for frame in 0..30 {
+            run_ui(&ctx, |ui| {
+                let mut state = CollapsingState::load_with_default_open(ui.ctx(), id, false);
+                if frame == 1 {
+                    state.set_open(true);
+                }
+                let expected = state.openness(ui.ctx());
+                let (_, header, _) = state
+                    .show_header_animated(ui, |_, openness| openness)
+                    .body(|ui| ui.label("Body"));
+                assert_eq!(header.inner, expected);
+                seen.push(header.inner);
+            });
+        }
+        assert!(seen
+            .iter()
+            .any(|&openness| 0.0 < openness && openness < 1.0));
+        assert_eq!(seen.last(), Some(&1.0));
+    }
+
+    #[test]
+    fn highlight_new_ends_at_deadline() {
+        let ctx = Context::default();
+        let show = |time: f64| {
+            let input = RawInput {
+                time: Some(time),
+                ..Default::default()
             };
+            let output = ctx.run(input, |ctx| {
+                CentralPanel::default().show(ctx, |ui| {
+                    CollapsingHeader::new("New setting")
+                        .highlight_new(10.0)
+                        .show(ui, |_| {});
+                });
+            });
+            let outlines = output
+                .shapes
+                .iter()
+                .filter(|clipped| matches!(&clipped.shape, Shape::Rect(rect) if !rect.stroke.is_empty()))
+                .count();
+            let repaint_delay = output.viewport_output[&ViewportId::ROOT].repaint_delay;
+            (outlines, repaint_delay.is_zero())
+        };
 
-            if let Some(ret_response) = ret_response {
-                CollapsingResponse {
-                    header_response,
-                    body_response: Some(ret_response.response),
-                    body_returned: Some(ret_response.inner),
-                    openness,
+        assert_eq!(show(1.0), (1, true));
+        assert_eq!(show(9.5), (1, true));
+        assert_eq!(show(10.5).0, 0);
+        // The repaint requested by the last highlighted frame has run out:
+        assert_eq!(show(10.6), (0, false));
+    }
+
+    #[test]
+    fn horizontal_layout_opens_sideways() {
+        let ctx = Context::default();
+        let show = || {
+            let mut result = None;
+            run_ui(&ctx, |ui| {
+                ui.horizontal(|ui| {
+                    let response = CollapsingHeader::new("Tools").show(ui, |ui| {
+                        ui.add_sized(vec2(200.0, 20.0), Button::new("Wide tool"));
+                    });
+                    result = Some((response.header_response, response.body_response));
+                });
+            });
+            result.unwrap()
+        };
+
+        let (header, _) = show();
+        let mut state = CollapsingState::load(&ctx, header.id).unwrap();
+        state.set_open(true);
+        state.store(&ctx);
+
+        let mut widths = vec![];
+        for _ in 0..30 {
+            let (header, body) = show();
+            let body = body.unwrap();
+            assert!(
+                header.rect.right() <= body.rect.left(),
+                "the body is to the right"
+            );
+            assert!(body.rect.top() < header.rect.bottom(), "on the same row");
+            widths.push(body.rect.width());
+        }
+        assert!(widths[0] < 200.0, "revealed from the left while animating");
+        assert_eq!(widths.last(), Some(&200.0));
+    }
+
+    #[test]
+    fn animation_time_per_header() {
+        let ctx = Context::default();
+        let show = |open: bool| {
+            let mut openness = vec![];
+            run_ui(&ctx, |ui| {
+                for animation_time in [0.05, 0.5] {
+                    let response = CollapsingHeader::new(format!("{animation_time}"))
+                        .open(Some(open))
+                        .animation_time(animation_time)
+                        .show(ui, |ui| ui.label("Body"));
+                    openness.push(response.openness);
                 }
-            } else {
-                CollapsingResponse {
-                    header_response,
-                    body_response: None,
-                    body_returned: None,
-                    openness,
+            });
+            openness
+        };
+
+        show(false);
+        let mut last = vec![];
+        for _ in 0..6 {
+            last = show(true);
+        }
+        let (fast, slow) = (last[0], last[1]);
+        assert_eq!(fast, 1.0, "done after 0.1 s");
+        assert!(slow < 1.0, "still opening after 0.1 s");
+    }
+
+    #[test]
+    fn reduce_collapse_animation_snaps_open() {
+        let ctx = Context::default();
+        ctx.options_mut(|options| options.reduce_collapse_animation = true);
+        let show = |open: bool| {
+            let mut result = None;
+            run_ui(&ctx, |ui| {
+                let response = CollapsingHeader::new("Instant")
+                    .open(Some(open))
+                    .show(ui, |ui| ui.allocate_space(vec2(50.0, 100.0)));
+                result = Some((
+                    response.openness,
+                    response.body_response.map(|r| r.rect.height()),
+                ));
+            });
+            result.unwrap()
+        };
+
+        assert_eq!(show(false), (0.0, None));
+        assert_eq!(show(true), (1.0, Some(100.0)), "no placeholder height");
+        assert_eq!(show(false), (0.0, None));
+    }
+
+    #[test]
+    fn lazy_body_is_skipped_while_animating() {
+        let ctx = Context::default();
+        let calls = Cell::new(0);
+        let show = |open: bool| {
+            let mut result = None;
+            run_ui(&ctx, |ui| {
+                let response = CollapsingHeader::new("Thumbnails")
+                    .open(Some(open))
+                    .lazy(true)
+                    .show(ui, |ui| {
+                        calls.set(calls.get() + 1);
+                        ui.allocate_space(vec2(50.0, 100.0));
+                    });
+                result = Some((response.openness, response.body_response.map(|r| r.rect)));
+            });
+            result.unwrap()
+        };
+
+        show(false);
+        let (openness, _) = show(true);
+        assert!(0.0 < openness && openness < 1.0);
+        assert_eq!(
+            calls.get(),
+            1,
+            "the first frame is built, to measure the body"
+        );
+
+        let mut heights = vec![];
+        let mut openness = 0.0;
+        while openness < 1.0 {
+            let (new_openness, body) = show(true);
+            openness = new_openness;
+            heights.push(body.unwrap().height());
+        }
+        assert_eq!(calls.get(), 2, "only built again once fully open");
+        assert!(
+            heights.windows(2).all(|pair| pair[0] <= pair[1]),
+            "{heights:?}"
+        );
+
+        let calls_when_open = calls.get();
+        while 0.0 < show(false).0 {}
+        assert_eq!(calls.get(), calls_when_open, "not built while closing");
+    }
+
+    #[test]
+    fn lazy_placeholder_is_as_wide_as_the_body() {
+        let ctx = Context::default();
+        let show = |open: bool| {
+            let mut result = None;
+            run_ui(&ctx, |ui| {
+                let response = CollapsingHeader::new("Thumbnails")
+                    .open(Some(open))
+                    .lazy(true)
+                    .show(ui, |ui| {
+                        ui.allocate_space(vec2(ui.available_width(), 100.0))
+                    });
+                result = Some((response.openness, response.body_response.map(|r| r.rect)));
+            });
+            result.unwrap()
+        };
+
+        show(false);
+        show(true); // measures the body
+        let (openness, placeholder) = show(true);
+        assert!(0.0 < openness && openness < 1.0);
+        while show(true).0 < 1.0 {}
+        let (_, body) = show(true);
+        let (placeholder, body) = (placeholder.unwrap(), body.unwrap());
+        assert_eq!(placeholder.x_range(), body.x_range());
+    }
+
+    #[test]
+    fn keyboard_toggles_focused_header() {
+        let ctx = Context::default();
+        let key = |key| Event::Key {
+            key,
+            physical_key: None,
+            pressed: true,
+            repeat: false,
+            modifiers: Modifiers::NONE,
+        };
+        let show = |events: Vec<Event>| {
+            let mut response = None;
+            run_frame(&ctx, events, |ui| {
+                response = Some(CollapsingHeader::new("Heading").show(ui, |ui| ui.label("Body")));
+            });
+            let response = response.unwrap();
+            let id = response.header_response.id;
+            CollapsingState::load(&ctx, id).map_or(false, |state| state.is_open())
+        };
+
+        show(vec![key(Key::Tab)]);
+        assert!(!show(vec![]));
+        for (pressed, open) in [
+            (Key::Enter, true),
+            (Key::Space, false),
+            (Key::ArrowRight, true),
+            (Key::ArrowRight, true),
+            (Key::ArrowLeft, false),
+        ] {
+            show(vec![key(pressed)]);
+            assert_eq!(show(vec![]), open, "after {pressed:?}");
+        }
+
+        let info = WidgetInfo::selected(WidgetType::CollapsingHeader, true, true, "Heading");
+        assert_eq!(info.description(), "Heading: expanded collapsing header");
+    }
+
+    #[test]
+    fn hidden_window_fades_out() {
+        let ctx = Context::default();
+        let show = || {
+            let mut shown = false;
+            run_ui(&ctx, |ui| {
+                let window = Window::new("Tools").show(ui.ctx(), |ui| ui.label("Body"));
+                shown = window.is_some();
+            });
+            shown
+        };
+        let id = Id::new("Tools").with("collapsing");
+
+        assert!(show());
+        let mut state = CollapsingState::load(&ctx, id).unwrap();
+        state.set_hidden(true);
+        state.store(&ctx);
+        assert!(show(), "still fading out");
+        let visibility = state.visibility(&ctx);
+        assert!(0.0 < visibility && visibility < 1.0, "{visibility}");
+        assert_eq!(state.openness(&ctx), 1.0, "hiding doesn't collapse");
+
+        for _ in 0..30 {
+            show();
+        }
+        assert!(!show());
+        assert!(CollapsingState::load(&ctx, id).unwrap().is_hidden());
+    }
+
+    #[test]
+    fn icon_on_the_right() {
+        for side in [HeaderIconSide::Left, HeaderIconSide::Right] {
+            let ctx = Context::default();
+            let mut header_rect = Rect::NOTHING;
+            let output = ctx.run(RawInput::default(), |ctx| {
+                CentralPanel::default().show(ctx, |ui| {
+                    let response = CollapsingHeader::new("Files")
+                        .icon_side(side)
+                        .show(ui, |ui| ui.label("Body"));
+                    header_rect = response.header_response.rect;
+                });
+            });
+            let arrow = output
+                .shapes
+                .iter()
+                .find(|clipped| matches!(clipped.shape, Shape::Path(_)))
+                .unwrap()
+                .shape
+                .visual_bounding_rect();
+            let icon_width = ctx.style().spacing.icon_width;
+            match side {
+                HeaderIconSide::Left => assert!(arrow.left() < header_rect.left() + icon_width),
+                HeaderIconSide::Right => assert!(arrow.right() > header_rect.right() - icon_width),
+            }
+        }
+    }
+
+    #[test]
+    fn just_finished_opening_and_closing_once() {
+        let ctx = Context::default();
+        let show = |open: bool| {
+            let mut result = None;
+            run_ui(&ctx, |ui| {
+                let response = CollapsingHeader::new("Header")
+                    .open(Some(open))
+                    .show(ui, |ui| ui.label("Body"));
+                result = Some((
+                    response.just_finished_opening(),
+                    response.just_finished_closing(),
+                ));
+            });
+            result.unwrap()
+        };
+        let count = |open: bool| {
+            let edges: Vec<_> = (0..60).map(|_| show(open)).collect();
+            (
+                edges.iter().filter(|edge| edge.0).count(),
+                edges.iter().filter(|edge| edge.1).count(),
+            )
+        };
+
+        assert_eq!(count(false), (0, 0));
+        assert_eq!(count(true), (1, 0));
+        assert_eq!(count(false), (0, 1));
+    }
+
+    #[test]
+    fn just_finished_opening_once_with_overshoot() {
+        let ctx = Context::default();
+        let mut openness = vec![];
+        let mut edges = 0;
+        for frame in 0..60 {
+            run_ui(&ctx, |ui| {
+                let response = CollapsingHeader::new("Header")
+                    .open(Some(0 < frame))
+                    .animation(AnimationStyle::Overshoot { amount: 1.7 })
+                    .show(ui, |ui| ui.label("Body"));
+                openness.push(response.openness);
+                edges += usize::from(response.just_finished_opening());
+            });
+        }
+        assert!(
+            openness.iter().any(|&openness| 1.0 < openness),
+            "{openness:?}"
+        );
+        assert_eq!(edges, 1);
+    }
+
+    #[test]
+    fn unmanaged_header_stores_no_openness() {
+        let ctx = Context::default();
+        run_ui(&ctx, |ui| {
+            let response = CollapsingHeader::new("Header")
+                .manage_state(false)
+                .show(ui, |ui| ui.label("Body"));
+            let prev_id = response.header_response.id.with("prev_openness");
+            assert!(ui.data(|d| d.get_temp::<f32>(prev_id)).is_none());
+        });
+    }
+
+    #[test]
+    fn set_all_open_includes_registered_regions() {
+        let ctx = Context::default();
+        let custom_id = Id::new("custom");
+        let show = || {
+            let mut open = vec![];
+            run_ui(&ctx, |ui| {
+                let response = CollapsingHeader::new("Outer").show(ui, |ui| {
+                    CollapsingHeader::new("Inner").show(ui, |ui| ui.label("Body"))
+                });
+                let state = CollapsingState::load_with_default_open(ui.ctx(), custom_id, false);
+                state.register(ui.ctx());
+                open.push(state.is_open());
+                state
+                    .show_header(ui, |ui| ui.label("Custom"))
+                    .body(|ui| ui.label("Body"));
+                open.push(response.fully_open());
+                if let Some(inner) = response.body_returned {
+                    open.push(inner.fully_open());
                 }
+            });
+            open
+        };
+
+        assert_eq!(show(), [false, false]);
+        CollapsingState::set_all_open(&ctx, true);
+        for _ in 0..30 {
+            show();
+        }
+        // The inner header was only shown once the outer one opened, so it takes another round:
+        CollapsingState::set_all_open(&ctx, true);
+        for _ in 0..30 {
+            show();
+        }
+        assert_eq!(show(), [true, true, true]);
+
+        CollapsingState::set_all_open(&ctx, false);
+        for _ in 0..30 {
+            show();
+        }
+        assert_eq!(show(), [false, false]);
+    }
+
+    #[test]
+    fn temp_state_is_not_persisted() {
+        let ctx = Context::default();
+        let show = || {
+            let mut id = Id::NULL;
+            run_ui(&ctx, |ui| {
+                id = CollapsingHeader::new("Node")
+                    .persist(false)
+                    .show(ui, |ui| ui.label("Body"))
+                    .header_response
+                    .id;
+            });
+            id
+        };
+        let id = show();
+        assert!(CollapsingState::load(&ctx, id).unwrap().state.temp);
+
+        // Loading and storing it elsewhere keeps it temporary:
+        CollapsingState::set_subtree_open(&ctx, Id::NULL, true);
+        let state = CollapsingState::load(&ctx, id).unwrap();
+        assert!(state.is_open() && state.state.temp);
+        show();
+        assert!(CollapsingState::load(&ctx, id).unwrap().state.temp);
+
+        let other = Id::new("other");
+        CollapsingState::load_with_default_open(&ctx, other, true).store_temp(&ctx);
+        assert!(CollapsingState::load(&ctx, other).unwrap().state.temp);
+        assert!(
+            CollapsingState::load_temp(&ctx, Id::new("new"), false)
+                .state
+                .temp
+        );
+    }
+
+    #[test]
+    fn show_header_selectable_selects_and_toggles_separately() {
+        let ctx = Context::default();
+        let id = Id::new("selectable");
+        let show = |events: Vec<Event>| {
+            let mut result = None;
+            run_frame(&ctx, events, |ui| {
+                let (row, header) = CollapsingState::load_with_default_open(ui.ctx(), id, false)
+                    .show_header_selectable(ui, false, |ui| ui.label("Header"));
+                let toggle_rect = header.toggle_button_response.rect;
+                header.body(|ui| ui.label("Body"));
+                result = Some((row.rect, toggle_rect, row.clicked()));
+            });
+            let (row_rect, toggle_rect, clicked) = result.unwrap();
+            let open = CollapsingState::load(&ctx, id).map_or(false, |state| state.is_open());
+            (row_rect, toggle_rect, clicked, open)
+        };
+        let click = |pos| click(pos, show);
+
+        let (row_rect, toggle_rect, _, _) = show(vec![]);
+        let (_, _, clicked, open) = click(row_rect.right_center() - vec2(10.0, 0.0));
+        assert!(
+            clicked && !open,
+            "clicking the row selects without toggling"
+        );
+        let (_, _, clicked, open) = click(toggle_rect.center());
+        assert!(
+            !clicked && open,
+            "clicking the button toggles without selecting"
+        );
+    }
+
+    #[test]
+    fn measure_open_height_without_opening() {
+        let ctx = Context::default();
+        let id = Id::new("measured");
+        let mut body_heights = vec![];
+        for frame in 0..3 {
+            run_ui(&ctx, |ui| {
+                let mut state = CollapsingState::load_with_default_open(ui.ctx(), id, false);
+                if frame == 0 {
+                    let cursor = ui.cursor();
+                    let height =
+                        state.measure_open_height(ui, |ui| ui.allocate_space(vec2(10.0, 100.0)));
+                    assert_eq!(height, 100.0);
+                    assert_eq!(ui.cursor(), cursor, "takes no space");
+                    assert!(!state.is_open());
+                    state.set_open(true);
+                }
+                let body =
+                    state.show_body_unindented(ui, |ui| ui.allocate_space(vec2(10.0, 100.0)));
+                if let Some(body) = body {
+                    body_heights.push(body.response.rect.height());
+                }
+            });
+        }
+        assert_eq!(
+            CollapsingState::load(&ctx, id).unwrap().state.open_height,
+            Some(100.0)
+        );
+        // Animating towards the measured height, rather than a placeholder:
+        let expected = 100.0 * CollapsingState::load(&ctx, id).unwrap().openness(&ctx);
+        assert!(
+            (body_heights.last().unwrap() - expected).abs() < 1.0,
+            "{body_heights:?}"
+        );
+    }
+
+    #[test]
+    fn custom_indent() {
+        let ctx = Context::default();
+        run_ui(&ctx, |ui| {
+            let default_indent = ui.spacing().indent;
+            let mut body_indent = 0.0;
+            let mut inner_spacing = 0.0;
+            let left = ui.min_rect().left();
+            let header = CollapsingHeader::new("Tree")
+                .indent(12.0)
+                .default_open(true);
+            let measured = header.measure(ui);
+            let response = header.show(ui, |ui| {
+                body_indent = ui.min_rect().left() - left;
+                inner_spacing = ui.spacing().indent;
+            });
+            assert_eq!(response.header_response.rect.size(), measured);
+            assert_eq!(body_indent, 12.0);
+            assert_eq!(inner_spacing, default_indent, "the body looks as usual");
+            assert_eq!(ui.spacing().indent, default_indent);
+        });
+    }
+
+    #[test]
+    fn header_double_clicked() {
+        let ctx = Context::default();
+        let show = |events: Vec<Event>| {
+            let mut result = None;
+            run_frame(&ctx, events, |ui| {
+                let response = CollapsingHeader::new("Folder").show(ui, |ui| ui.label("Body"));
+                let id = response.header_response.id;
+                let open = CollapsingState::load(ui.ctx(), id).map_or(false, |s| s.is_open());
+                result = Some((
+                    response.header_response.rect,
+                    response.header_double_clicked(),
+                    open,
+                ));
+            });
+            result.unwrap()
+        };
+        let (rect, _, _) = show(vec![]);
+        let (_, double_clicked, open) = click(rect.center(), show);
+        assert!(!double_clicked && open, "the first click toggles as usual");
+        let (_, double_clicked, open) = click(rect.center(), show);
+        assert!(double_clicked && !open);
+    }
+
+    #[test]
+    fn right_to_left_layout_mirrors_header() {
+        let ctx = Context::default();
+        let mut header_rect = Rect::NOTHING;
+        let mut body_right = 0.0;
+        let output = ctx.run(RawInput::default(), |ctx| {
+            CentralPanel::default().show(ctx, |ui| {
+                ui.with_layout(Layout::top_down(Align::Max), |ui| {
+                    let response =
+                        CollapsingHeader::new("عنوان")
+                            .default_open(true)
+                            .show(ui, |ui| {
+                                body_right = ui.max_rect().right();
+                                ui.label("Body")
+                            });
+                    header_rect = response.header_response.rect;
+                });
+            });
+        });
+        let arrow = output
+            .shapes
+            .iter()
+            .find(|clipped| matches!(clipped.shape, Shape::Path(_)))
+            .unwrap()
+            .shape
+            .visual_bounding_rect();
+        let indent = ctx.style().spacing.indent;
+        assert!(
+            arrow.left() > header_rect.right() - indent,
+            "{arrow:?} {header_rect:?}"
+        );
+        assert_eq!(body_right, header_rect.right() - indent);
+    }
+
+    #[test]
+    fn retain_removes_gone_states() {
+        let ctx = Context::default();
+        let show = |rows: &[&str]| {
+            let mut ids = vec![];
+            run_ui(&ctx, |ui| {
+                let list = CollapsingHeader::new("List")
+                    .default_open(true)
+                    .show(ui, |ui| {
+                        for &row in rows {
+                            let row = CollapsingHeader::new(row).show(ui, |ui| {
+                                CollapsingHeader::new("Nested").show(ui, |ui| ui.label("Body"))
+                            });
+                            ids.push(row.header_response.id);
+                        }
+                    });
+                let other = CollapsingHeader::new("Other").show(ui, |ui| ui.label("Body"));
+                ids.push(list.header_response.id);
+                ids.push(other.header_response.id);
+            });
+            ids
+        };
+        let ids = show(&["a", "b", "c"]);
+        let (a, b, c, list, other) = (ids[0], ids[1], ids[2], ids[3], ids[4]);
+        CollapsingState::set_user_data(&ctx, b, 7_u32);
+        CollapsingState::load(&ctx, c).unwrap().remove(&ctx);
+        show(&["a"]);
+
+        let mut asked = vec![];
+        CollapsingState::retain(&ctx, list, |id| {
+            asked.push(id);
+            id == a
+        });
+        asked.sort_by_key(|id| id.value());
+        let mut expected = vec![a, b];
+        expected.sort_by_key(|id| id.value());
+        assert_eq!(
+            asked, expected,
+            "only the rows of the list, without the removed one"
+        );
+        assert!(CollapsingState::load(&ctx, a).is_some());
+        assert!(CollapsingState::load(&ctx, b).is_none());
+        assert_eq!(CollapsingState::user_data::<u32>(&ctx, b), None);
+        assert_eq!(
+            ctx.data(|d| d.get_temp::<f32>(b.with("prev_openness"))),
+            None
+        );
+        assert!(ctx
+            .data(|d| d.get_temp::<f32>(a.with("prev_openness")))
+            .is_some());
+        assert!(CollapsingState::load(&ctx, list).is_some());
+        assert!(
+            CollapsingState::load(&ctx, other).is_some(),
+            "not in the list"
+        );
+    }
+
+    #[test]
+    fn registry_forgets_headers_that_are_gone() {
+        let ctx = Context::default();
+        for frame in 0..10 {
+            run_ui(&ctx, |ui| {
+                // Rows that come and go, without ever calling `retain`:
+                CollapsingHeader::new(format!("Row {frame}")).show(ui, |_| {});
+            });
+        }
+        let known = HeaderRegistry::read(&ctx, |registry| registry.recent_children(Id::NULL));
+        assert_eq!(known.len(), 2, "only the last two frames");
+    }
+
+    #[test]
+    fn frame_style_overrides() {
+        let frame = |header: CollapsingHeader| {
+            let ctx = Context::default();
+            let output = ctx.run(RawInput::default(), |ctx| {
+                CentralPanel::default()
+                    .frame(Frame::none())
+                    .show(ctx, |ui| header.show_background(true).show(ui, |_| {}));
+            });
+            output
+                .shapes
+                .iter()
+                .find_map(|clipped| match &clipped.shape {
+                    // Skip the empty frame of the panel:
+                    Shape::Rect(rect) if rect.fill != Color32::TRANSPARENT => Some(*rect),
+                    _ => None,
+                })
+                .unwrap()
+        };
+
+        let default = frame(CollapsingHeader::new("Group"));
+        let custom = frame(
+            CollapsingHeader::new("Group")
+                .frame_fill(Color32::RED)
+                .frame_stroke(Stroke::new(2.0, Color32::BLUE))
+                .frame_rounding(0.0),
+        );
+        assert_eq!(custom.fill, Color32::RED);
+        assert_eq!(custom.stroke, Stroke::new(2.0, Color32::BLUE));
+        assert_eq!(custom.rounding, Rounding::ZERO);
+        assert_ne!(default.fill, custom.fill);
+        assert_ne!(default.rounding, custom.rounding);
+    }
+
+    #[test]
+    fn show_header_reports_toggle() {
+        let ctx = Context::default();
+        let id = Id::new("custom_header");
+        let show = |events: Vec<Event>| {
+            let mut result = None;
+            run_frame(&ctx, events, |ui| {
+                let header = CollapsingState::load_with_default_open(ui.ctx(), id, false)
+                    .show_header(ui, |ui| ui.label("Header"));
+                let toggled = header.toggled();
+                let (toggle, _, _) = header.body(|ui| ui.label("Body"));
+                assert_eq!(toggle.changed(), toggled);
+                result = Some((toggle.rect, toggled));
+            });
+            result.unwrap()
+        };
+        let (rect, toggled) = show(vec![]);
+        assert!(!toggled);
+        assert!(click(rect.center(), show).1);
+        assert!(!show(vec![]).1);
+    }
+
+    #[test]
+    fn estimated_open_height_is_corrected() {
+        let ctx = Context::default();
+        let id = Id::new("estimated");
+        let mut body_heights = vec![];
+        for frame in 0..40 {
+            run_ui(&ctx, |ui| {
+                let mut state = CollapsingState::load_with_default_open(ui.ctx(), id, false);
+                state.set_estimated_open_height(50.0);
+                if frame == 1 {
+                    state.set_open(true);
+                }
+                let body =
+                    state.show_body_unindented(ui, |ui| ui.allocate_space(vec2(10.0, 100.0)));
+                if let Some(body) = body {
+                    body_heights.push(body.response.rect.height());
+                }
+            });
+        }
+        // The first frame of opening uses the estimate, not a placeholder:
+        let first_openness = body_heights[0] / 50.0;
+        assert!(
+            0.0 < first_openness && first_openness < 1.0,
+            "{body_heights:?}"
+        );
+        assert_eq!(*body_heights.last().unwrap(), 100.0);
+        let state = CollapsingState::load(&ctx, id).unwrap();
+        assert_eq!(state.state.open_height, Some(100.0));
+    }
+
+    #[test]
+    fn known_open_height_skips_the_placeholder() {
+        let ctx = Context::default();
+        let mut first_body = None;
+        for frame in 0..2 {
+            run_ui(&ctx, |ui| {
+                let response = CollapsingHeader::new("Fixed")
+                    .open(Some(frame == 1))
+                    .known_open_height(100.0)
+                    .show(ui, |ui| ui.allocate_space(vec2(10.0, 100.0)));
+                if let Some(body) = response.body_response {
+                    first_body = Some((response.openness, body.rect.height()));
+                }
+            });
+        }
+        let (openness, height) = first_body.unwrap();
+        assert!(0.0 < openness && openness < 1.0);
+        assert!(
+            (height - openness * 100.0).abs() < 1.0,
+            "{height} at openness {openness}"
+        );
+    }
+
+    #[test]
+    fn nested_body_stays_inside_parent_clip() {
+        for sideways in [false, true] {
+            nested_body_stays_inside_parent_clip_in(sideways);
+        }
+    }
+
+    fn nested_body_stays_inside_parent_clip_in(sideways: bool) {
+        let ctx = Context::default();
+        let show = |open: Option<bool>| {
+            let mut clips = (Rect::NOTHING, Rect::NOTHING);
+            run_ui(&ctx, |ui| {
+                let layout = if sideways {
+                    Layout::left_to_right(Align::Min)
+                } else {
+                    Layout::top_down(Align::Min)
+                };
+                ui.with_layout(layout, |ui| {
+                    CollapsingHeader::new("Parent").open(open).show(ui, |ui| {
+                        clips.0 = ui.clip_rect();
+                        ui.label("Parent body");
+                        CollapsingHeader::new("Child").open(open).show(ui, |ui| {
+                            clips.1 = ui.clip_rect();
+                            for i in 0..20 {
+                                ui.label(format!("Line {i}"));
+                            }
+                        });
+                    });
+                });
+            });
+            clips
+        };
+
+        show(None);
+        // Open both in the same frame, then let them animate:
+        for i in 0..30 {
+            let (parent, child) = show((i == 0).then_some(true));
+            if child != Rect::NOTHING {
+                assert!(parent.contains_rect(child), "{child:?} outside {parent:?}");
             }
-        })
-        .inner
+        }
+        // And close both in the same frame:
+        let mut animating = 0;
+        for i in 0..30 {
+            let (parent, child) = show((i == 0).then_some(false));
+            if child != Rect::NOTHING {
+                assert!(parent.contains_rect(child), "{child:?} outside {parent:?}");
+                animating += 1;
+            }
+        }
+        assert!(0 < animating, "the child was shown while closing");
     }
-}
 
-/// The response from showing a [`CollapsingHeader`].
-pub struct CollapsingResponse<R> {
-    /// Response of the actual clickable header.
-    pub header_response: Response,
+    #[test]
+    fn show_header_full_click_toggles_on_background_only() {
+        let ctx = Context::default();
+        let id = Id::new("full_click");
+        let show = |events: Vec<Event>| {
+            let mut result = None;
+            run_frame(&ctx, events, |ui| {
+                let mut button_rect = Rect::NOTHING;
+                let header = CollapsingState::load_with_default_open(ui.ctx(), id, false)
+                    .show_header_full_click(ui, |ui| {
+                        ui.label("Header");
+                        button_rect = ui.button("Button").rect;
+                    });
+                let toggled = header.toggled();
+                let (_, header_response, _) = header.body(|ui| ui.label("Body"));
+                result = Some((header_response.response.rect, button_rect, toggled));
+            });
+            let (row_rect, button_rect, toggled) = result.unwrap();
+            let open = CollapsingState::load(&ctx, id).map_or(false, |state| state.is_open());
+            (row_rect, button_rect, toggled, open)
+        };
+        let click = |pos| click(pos, show);
 
-    /// None iff collapsed.
-    pub body_response: Option<Response>,
+        let (row_rect, button_rect, _, _) = show(vec![]);
+        let label_pos = pos2(
+            row_rect.left() + ctx.style().spacing.indent + 5.0,
+            row_rect.center().y,
+        );
+        let (_, _, toggled, open) = click(label_pos);
+        assert!(toggled && open, "clicking the header label toggles");
+        let (_, _, toggled, open) = click(button_rect.center());
+        assert!(!toggled && open, "clicking a header button doesn't toggle");
+        let (_, _, toggled, open) = click(label_pos);
+        assert!(toggled && !open);
+    }
 
-    /// None iff collapsed.
-    pub body_returned: Option<R>,
+    #[test]
+    fn open_once_lets_the_user_close_again() {
+        let ctx = Context::default();
+        let show = |open_once: Option<bool>, events: Vec<Event>| {
+            let mut response = None;
+            run_frame(&ctx, events, |ui| {
+                response = Some(
+                    CollapsingHeader::new("Header")
+                        .set_open_once(open_once)
+                        .show(ui, |ui| ui.label("Body")),
+                );
+            });
+            let response = response.unwrap();
+            (response.header_response.rect, response.fully_closed())
+        };
+        let click = |open_once: Option<bool>, rect: Rect| {
+            click(rect.center(), |events| show(open_once, events));
+        };
+        let (rect, closed) = show(None, vec![]);
+        assert!(closed);
+        show(Some(true), vec![]);
+        for _ in 0..30 {
+            show(Some(true), vec![]);
+        }
+        assert!(!show(Some(true), vec![]).1, "opened once requested");
 
-    /// 0.0 if fully closed, 1.0 if fully open, and something in-between while animating.
-    pub openness: f32,
-}
+        click(Some(true), rect);
+        for _ in 0..30 {
+            show(Some(true), vec![]);
+        }
+        assert!(show(Some(true), vec![]).1, "the user closed it again");
 
-impl<R> CollapsingResponse<R> {
-    /// Was the [`CollapsingHeader`] fully closed (and not being animated)?
-    pub fn fully_closed(&self) -> bool {
-        self.openness <= 0.0
+        show(None, vec![]);
+        show(Some(true), vec![]);
+        for _ in 0..30 {
+            show(Some(true), vec![]);
+        }
+        assert!(!show(Some(true), vec![]).1, "a new request opens it again");
     }
 
-    /// Was the [`CollapsingHeader`] fully open (and not being animated)?
-    pub fn fully_open(&self) -> bool {
-        self.openness >= 1.0
+    #[test]
+    fn scroll_to_body_on_open() {
+        let ctx = Context::default();
+        let show = |open: Option<bool>| {
+            let mut result = None;
+            run_ui(&ctx, |ui| {
+                let output = ScrollArea::vertical().max_height(200.0).show(ui, |ui| {
+                    ui.allocate_space(vec2(100.0, 1000.0));
+                    let header = CollapsingHeader::new("Deep")
+                        .open(open)
+                        .scroll_to_body_on_open(true)
+                        .show(ui, |ui| ui.allocate_space(vec2(100.0, 100.0)))
+                        .header_response
+                        .rect;
+                    ui.allocate_space(vec2(100.0, 1000.0));
+                    header
+                });
+                result = Some((output.inner, output.inner_rect));
+            });
+            result.unwrap()
+        };
+
+        let (header, clip) = show(None);
+        assert!(
+            !clip.intersects(header),
+            "starts out of view: {header:?} {clip:?}"
+        );
+        show(Some(true));
+        for _ in 0..30 {
+            show(None);
+        }
+        let (header, clip) = show(None);
+        assert!(
+            clip.top() <= header.top() && header.top() < clip.top() + 10.0,
+            "header scrolled to the top: {header:?} {clip:?}"
+        );
+    }
+
+    #[test]
+    fn icon_color_ignores_hover() {
+        let ctx = Context::default();
+        let color = Color32::from_gray(120);
+        let arrow = |pos: Pos2, styled: bool| {
+            let input = RawInput {
+                events: vec![Event::PointerMoved(pos)],
+                time: Some(ctx.input(|i| i.time) + 1.0 / 60.0),
+                ..Default::default()
+            };
+            let output = ctx.run(input, |ctx| {
+                CentralPanel::default().show(ctx, |ui| {
+                    let mut header = CollapsingHeader::new("Header");
+                    if styled {
+                        header = header.icon_color(color).icon_scale(1.0);
+                    }
+                    header.show(ui, |ui| ui.label("Body"));
+                });
+            });
+            output
+                .shapes
+                .iter()
+                .find_map(|clipped| match &clipped.shape {
+                    Shape::Path(path) => Some((path.fill, path.visual_bounding_rect())),
+                    _ => None,
+                })
+                .unwrap()
+        };
+
+        let away = pos2(-10.0, -10.0);
+        let (_, default_bounds) = arrow(away, false);
+        let (fill, bounds) = arrow(away, true);
+        assert_eq!(fill, color);
+        assert!(bounds.width() > default_bounds.width(), "{bounds:?}");
+        arrow(bounds.center(), true);
+        assert_eq!(
+            arrow(bounds.center(), true).0,
+            color,
+            "same color when hovered"
+        );
+        assert_ne!(arrow(bounds.center(), false).0, color);
+    }
+
+    #[test]
+    fn storing_unchanged_state_keeps_it() {
+        let ctx = Context::default();
+        let id = Id::new("unchanged");
+        let mut state = CollapsingState::load_with_default_open(&ctx, id, false);
+        state.store(&ctx);
+        state.store(&ctx);
+        assert!(!CollapsingState::load(&ctx, id).unwrap().is_open());
+
+        state.set_open(true);
+        state.store(&ctx);
+        state.store(&ctx);
+        assert!(CollapsingState::load(&ctx, id).unwrap().is_open());
+
+        state.remove(&ctx);
+        assert!(CollapsingState::load(&ctx, id).is_none());
+        state.store(&ctx);
+        assert!(
+            CollapsingState::load(&ctx, id).unwrap().is_open(),
+            "stored again after being removed"
+        );
+    }
+
+    #[test]
+    fn header_rows_dont_clash_with_themselves() {
+        let ctx = Context::default();
+        ctx.options_mut(|o| o.warn_on_id_clash = true);
+        for _ in 0..3 {
+            let input = RawInput {
+                time: Some(ctx.input(|i| i.time) + 1.0 / 60.0),
+                ..Default::default()
+            };
+            let output = ctx.run(input, |ctx| {
+                CentralPanel::default().show(ctx, |ui| {
+                    CollapsingState::load_with_default_open(ui.ctx(), Id::new("selectable"), false)
+                        .show_header_selectable(ui, true, |ui| ui.label("Selectable"))
+                        .1
+                        .body(|ui| ui.label("Body"));
+                    CollapsingState::load_with_default_open(ui.ctx(), Id::new("full"), false)
+                        .show_header_full_click(ui, |ui| ui.label("Full click"))
+                        .body(|ui| ui.label("Body"));
+                });
+            });
+            let clash = output.shapes.iter().any(|clipped| {
+                matches!(&clipped.shape, Shape::Text(text) if text.galley.text().contains("use of widget ID"))
+            });
+            assert!(!clash, "no id clash warning");
+        }
+    }
+
+    #[test]
+    fn retain_reaches_unchanged_loaded_states() {
+        let show = |ctx: &Context| {
+            let mut id = Id::NULL;
+            run_ui(ctx, |ui| {
+                id = CollapsingHeader::new("Loaded")
+                    .show(ui, |ui| ui.label("Body"))
+                    .header_response
+                    .id;
+            });
+            id
+        };
+        let earlier = Context::default();
+        let id = show(&earlier);
+        let state = CollapsingState::load(&earlier, id).unwrap().state;
+
+        // Like a state loaded from an earlier save, which is then stored unchanged:
+        let ctx = Context::default();
+        ctx.data_mut(|d| d.insert_persisted(id, state));
+        show(&ctx);
+        show(&ctx);
+        CollapsingState::retain(&ctx, Id::NULL, |_| false);
+        assert!(CollapsingState::load(&ctx, id).is_none());
     }
 }
@@ -0,0 +1,83 @@
+use super::CollapsingState;
+use crate::*;
+
+/// Show the body in a popup below the header, see [`CollapsingHeader::flyout`].
+pub(super) fn show_flyout<R>(
+    ui: &Ui,
+    id: Id,
+    header_response: &Response,
+    state: &mut CollapsingState,
+    openness: f32,
+    add_body: impl FnOnce(&mut Ui) -> R,
+) -> Option<InnerResponse<R>> {
+    if openness <= 0.0 {
+        state.store(ui.ctx()); // like `show_body_unindented` does when closed
+        return None;
+    }
+
+    let frame = Frame::popup(ui.style());
+    let min_width = header_response.rect.width() - frame.total_margin().sum().x;
+    let area = Area::new(id.with("flyout"))
+        .order(Order::Foreground)
+        .fixed_pos(header_response.rect.left_bottom())
+        .show(ui.ctx(), |ui| {
+            frame
+                .show(ui, |ui| {
+                    ui.set_min_width(min_width);
+                    state.show_body_unindented(ui, add_body)
+                })
+                .inner
+        });
+
+    let dismissed = area.response.clicked_elsewhere() && !header_response.hovered()
+        || ui.input(|i| i.key_pressed(Key::Escape));
+    if state.is_open() && dismissed {
+        state.set_open(false);
+        state.store(ui.ctx());
+        ui.ctx().request_repaint();
+    }
+    area.inner
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{click, run_frame};
+
+    #[test]
+    fn flyout_does_not_move_siblings() {
+        let ctx = Context::default();
+        let show = |events: Vec<Event>| {
+            let mut result = None;
+            run_frame(&ctx, events, |ui| {
+                let response = CollapsingHeader::new("Flyout")
+                    .flyout(true)
+                    .show(ui, |ui| ui.allocate_space(vec2(50.0, 100.0)));
+                let below = ui.label("Below");
+                result = Some((response.header_response, response.body_response, below.rect));
+            });
+            result.unwrap()
+        };
+        let click = |pos| click(pos, show);
+
+        let (header, body, below_closed) = show(vec![]);
+        assert!(body.is_none());
+        click(header.rect.center());
+        for _ in 0..30 {
+            show(vec![]);
+        }
+        let (header, body, below_open) = show(vec![]);
+        let body = body.unwrap();
+        assert_eq!(below_open, below_closed);
+        assert!(body.rect.top() >= header.rect.bottom());
+        assert!(
+            body.rect.intersects(below_open),
+            "the flyout floats over the siblings"
+        );
+
+        // Clicking elsewhere closes it:
+        click(pos2(500.0, 500.0));
+        let id = header.id;
+        assert!(!CollapsingState::load(&ctx, id).unwrap().is_open());
+    }
+}
@@ -0,0 +1,187 @@
+use std::hash::Hash;
+
+use super::{GroupedHeader, HeaderRegistry};
+use crate::*;
+
+/// A group of [`CollapsingHeader`]s that can be navigated together with the keyboard.
+///
+/// While one of the headers in the group has keyboard focus, typing moves the focus
+/// to the next header whose title starts with what was typed (like in a file explorer).
+/// Typing the same letter repeatedly cycles through the headers starting with that letter.
+///
+/// ```
+/// # egui::__run_test_ui(|ui| {
+/// egui::collapsing_header::CollapsingGroup::new("settings").show(ui, |ui| {
+///     ui.collapsing("Audio", |ui| ui.label("…"));
+///     ui.collapsing("Video", |ui| ui.label("…"));
+/// });
+/// # });
+/// ```
+#[must_use = "You should call .show()"]
+pub struct CollapsingGroup {
+    id_source: Id,
+    align_values: bool,
+}
+
+impl CollapsingGroup {
+    /// How long after the last key press the typed text is forgotten.
+    const TYPE_AHEAD_TIMEOUT: f64 = 1.0;
+
+    pub fn new(id_source: impl Hash) -> Self {
+        Self {
+            id_source: Id::new(id_source),
+            align_values: false,
+        }
+    }
+
+    /// Line up the [`CollapsingHeader::value`]s of the headers in the group in a column,
+    /// like in a property grid. Default: `false`.
+    ///
+    /// The column is placed after the widest title, as measured in the previous frame,
+    /// so it takes a frame to settle when the titles change.
+    #[inline]
+    pub fn align_values(mut self, align_values: bool) -> Self {
+        self.align_values = align_values;
+        self
+    }
+
+    /// Show the group. All [`CollapsingHeader`]s shown inside the closure,
+    /// including nested ones, belong to the group.
+    pub fn show<R>(self, ui: &mut Ui, add_contents: impl FnOnce(&mut Ui) -> R) -> InnerResponse<R> {
+        let id = ui.make_persistent_id(self.id_source);
+
+        HeaderRegistry::write(ui.ctx(), |registry| registry.groups.push(id));
+        let response = ui.scope(add_contents);
+        let headers = HeaderRegistry::write(ui.ctx(), |registry| {
+            registry.groups.pop();
+            registry.group_headers.remove(&id).unwrap_or_default()
+        });
+
+        Self::type_ahead(ui, id, &headers);
+
+        let column_id = id.with("value_column");
+        if self.align_values {
+            let column = headers.iter().filter_map(|h| h.value_x).reduce(f32::max);
+            let previous = ui.data(|d| d.get_temp::<f32>(column_id));
+            if column != previous {
+                ui.ctx().request_repaint(); // settle next frame
+            }
+            ui.data_mut(|d| match column {
+                Some(column) => d.insert_temp(column_id, column),
+                None => d.remove::<f32>(column_id),
+            });
+        } else {
+            ui.data_mut(|d| d.remove::<f32>(column_id));
+        }
+
+        response
+    }
+
+    fn type_ahead(ui: &Ui, id: Id, headers: &[GroupedHeader]) {
+        let focused = ui.memory(|mem| mem.focused());
+        let Some(current) = headers.iter().position(|h| Some(h.id) == focused) else {
+            return;
+        };
+
+        let typed: String = ui.input(|i| {
+            i.events
+                .iter()
+                .filter_map(|event| match event {
+                    Event::Text(text) => Some(text.as_str()),
+                    _ => None,
+                })
+                .collect()
+        });
+        if typed.is_empty() {
+            return;
+        }
+
+        let buffer_id = id.with("type_ahead");
+        let time = ui.input(|i| i.time);
+        let mut buffer = ui
+            .data(|d| d.get_temp::<(f64, String)>(buffer_id))
+            .filter(|(last_time, _)| time - last_time < Self::TYPE_AHEAD_TIMEOUT)
+            .map(|(_, buffer)| buffer)
+            .unwrap_or_default();
+        buffer.push_str(&typed);
+
+        let labels: Vec<&str> = headers.iter().map(|h| h.label.as_str()).collect();
+        if let Some(index) = type_ahead_match(&labels, current, &buffer) {
+            let header = &headers[index];
+            ui.memory_mut(|mem| mem.request_focus(header.id));
+            ui.scroll_to_rect(header.rect, None);
+            ui.ctx().request_repaint();
+        }
+
+        ui.data_mut(|d| d.insert_temp(buffer_id, (time, buffer)));
+    }
+}
+
+/// Find the label to jump to when `typed` has been typed while `labels[current]` is focused.
+///
+/// Typing a single letter (or the same letter repeatedly) jumps to the next label starting with it.
+/// Typing a longer prefix finds the first label starting with it, starting at the current one.
+fn type_ahead_match(labels: &[&str], current: usize, typed: &str) -> Option<usize> {
+    let typed = typed.to_lowercase();
+    let mut chars = typed.chars();
+    let first = chars.next()?;
+    let (prefix, start) = if chars.all(|c| c == first) {
+        (first.to_string(), current + 1)
+    } else {
+        (typed.clone(), current)
+    };
+
+    (0..labels.len())
+        .map(|offset| (start + offset) % labels.len())
+        .find(|&index| labels[index].to_lowercase().starts_with(&prefix))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn type_ahead_matching() {
+        let labels = ["Audio", "Advanced", "Video", "Accessibility"];
+        assert_eq!(type_ahead_match(&labels, 0, "v"), Some(2));
+        assert_eq!(type_ahead_match(&labels, 0, "a"), Some(1));
+        assert_eq!(type_ahead_match(&labels, 1, "aa"), Some(3));
+        assert_eq!(type_ahead_match(&labels, 3, "a"), Some(0));
+        assert_eq!(type_ahead_match(&labels, 1, "acc"), Some(3));
+        assert_eq!(type_ahead_match(&labels, 0, "au"), Some(0));
+        assert_eq!(type_ahead_match(&labels, 0, "x"), None);
+        assert_eq!(type_ahead_match(&labels, 0, ""), None);
+    }
+
+    #[test]
+    fn align_values_lines_up_values() {
+        let ctx = Context::default();
+        let mut value_xs = vec![];
+        for _ in 0..3 {
+            let output = ctx.run(RawInput::default(), |ctx| {
+                CentralPanel::default().show(ctx, |ui| {
+                    CollapsingGroup::new("group")
+                        .align_values(true)
+                        .show(ui, |ui| {
+                            CollapsingHeader::new("A").value("1").show(ui, |_| {});
+                            CollapsingHeader::new("A much longer title")
+                                .value("2")
+                                .show(ui, |_| {});
+                        });
+                });
+            });
+            value_xs = output
+                .shapes
+                .iter()
+                .filter_map(|clipped| match &clipped.shape {
+                    Shape::Text(text) if ["1", "2"].contains(&text.galley.text()) => {
+                        Some(text.pos.x)
+                    }
+                    _ => None,
+                })
+                .collect();
+        }
+        assert_eq!(value_xs.len(), 2);
+        assert_eq!(value_xs[0], value_xs[1]);
+    }
+}
@@ -0,0 +1,182 @@
+use crate::*;
+
+/// A [`CollapsingHeader`] that is open when `current` equals its variant, for accordions over an enum.
+///
+/// Clicking the header selects its variant, which opens it and closes the headers of the other variants.
+/// The sections animate as usual.
+/// Use [`Self::keep_mounted`] to keep the widget state of the other sections while they are closed.
+///
+/// ```
+/// # egui::__run_test_ui(|ui| {
+/// use egui::collapsing_header::CollapsingRadio;
+///
+/// #[derive(Clone, Copy, PartialEq)]
+/// enum Section {
+///     General,
+///     Advanced,
+/// }
+///
+/// let mut section = Section::General;
+/// CollapsingRadio::new(&mut section, Section::General, "General").show(ui, |ui| ui.label("…"));
+/// CollapsingRadio::new(&mut section, Section::Advanced, "Advanced").show(ui, |ui| ui.label("…"));
+/// # });
+/// ```
+#[must_use = "You should call .show()"]
+pub struct CollapsingRadio<'a, T> {
+    current: &'a mut T,
+    variant: T,
+    header: CollapsingHeader,
+}
+
+impl<'a, T: PartialEq + Copy> CollapsingRadio<'a, T> {
+    pub fn new(current: &'a mut T, variant: T, text: impl Into<WidgetText>) -> Self {
+        Self {
+            current,
+            variant,
+            header: CollapsingHeader::new(text),
+        }
+    }
+
+    /// Change the [`CollapsingHeader`], e.g. to give it an [`CollapsingHeader::id_source`].
+    ///
+    /// Its [`CollapsingHeader::open`] is overridden.
+    #[inline]
+    pub fn header(mut self, header: impl FnOnce(CollapsingHeader) -> CollapsingHeader) -> Self {
+        self.header = header(self.header);
+        self
+    }
+
+    /// Keep the widgets of this section alive while another variant is selected,
+    /// so they don't lose their state when switching back.
+    ///
+    /// See [`CollapsingHeader::keep_mounted`] for the cost of this.
+    #[inline]
+    pub fn keep_mounted(mut self, keep_mounted: bool) -> Self {
+        self.header = self.header.keep_mounted(keep_mounted);
+        self
+    }
+
+    pub fn show<R>(
+        self,
+        ui: &mut Ui,
+        add_body: impl FnOnce(&mut Ui) -> R,
+    ) -> CollapsingResponse<R> {
+        let Self {
+            current,
+            variant,
+            header,
+        } = self;
+        let response = header.open(Some(*current == variant)).show(ui, add_body);
+        // The arrow has a response of its own, on top of the header:
+        let clicked =
+            response.header_response.clicked() || response.toggle_or_header_response().clicked();
+        if clicked && *current != variant {
+            *current = variant;
+            ui.ctx().request_repaint();
+        }
+        response
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{click, run_frame};
+
+    #[test]
+    fn collapsing_radio_opens_one_variant() {
+        #[derive(Clone, Copy, Debug, PartialEq)]
+        enum Section {
+            A,
+            B,
+            C,
+        }
+
+        let ctx = Context::default();
+        let mut current = Section::A;
+        let mut show = |events: Vec<Event>| {
+            let mut shown = vec![];
+            let mut rects = vec![];
+            run_frame(&ctx, events, |ui| {
+                for (variant, text) in [(Section::A, "A"), (Section::B, "B"), (Section::C, "C")] {
+                    let response = CollapsingRadio::new(&mut current, variant, text)
+                        .show(ui, |ui| ui.label("Body"));
+                    shown.push(response.fully_open());
+                    rects.push((
+                        response.header_response.rect,
+                        response.toggle_or_header_response().rect,
+                    ));
+                }
+            });
+            (shown, rects, current)
+        };
+        let settle =
+            |show: &mut dyn FnMut(Vec<Event>) -> (Vec<bool>, Vec<(Rect, Rect)>, Section)| {
+                for _ in 0..30 {
+                    show(vec![]);
+                }
+                show(vec![])
+            };
+
+        let (shown, _, _) = settle(&mut show);
+        assert_eq!(shown, vec![true, false, false]);
+
+        // Click the header of C and B, and the arrow of A:
+        for (index, variant) in [(2, Section::C), (1, Section::B), (0, Section::A)] {
+            let (_, rects, _) = show(vec![]);
+            let (header_rect, arrow_rect) = rects[index];
+            let rect = if index == 0 { arrow_rect } else { header_rect };
+            click(rect.center(), &mut show);
+            let (shown, _, current) = settle(&mut show);
+            assert_eq!(current, variant);
+            let expected: Vec<bool> = (0..3).map(|i| i == index).collect();
+            assert_eq!(shown, expected);
+        }
+    }
+
+    #[test]
+    fn keep_mounted_radio_keeps_closed_section_alive() {
+        #[derive(Clone, Copy, PartialEq)]
+        enum Tab {
+            Notes,
+            Other,
+        }
+
+        let ctx = Context::default();
+        let mut notes = String::new();
+        let show = |tab: Tab, notes: &mut String, events: Vec<Event>| {
+            let mut tab = tab;
+            let mut notes_shown = false;
+            let mut notes_id = None;
+            run_frame(&ctx, events, |ui| {
+                CollapsingRadio::new(&mut tab, Tab::Notes, "Notes")
+                    .keep_mounted(true)
+                    .show(ui, |ui| {
+                        notes_shown = true;
+                        notes_id = Some(ui.text_edit_singleline(notes).id);
+                    });
+                CollapsingRadio::new(&mut tab, Tab::Other, "Other")
+                    .keep_mounted(true)
+                    .show(ui, |ui| ui.label("Other"));
+            });
+            (notes_shown, notes_id.unwrap())
+        };
+
+        let (_, notes_id) = show(Tab::Notes, &mut notes, vec![]);
+        ctx.memory_mut(|mem| mem.request_focus(notes_id));
+        show(Tab::Notes, &mut notes, vec![]);
+        show(
+            Tab::Notes,
+            &mut notes,
+            vec![Event::Text("draft".to_owned())],
+        );
+        assert_eq!(notes, "draft");
+
+        for _ in 0..30 {
+            let (notes_shown, _) = show(Tab::Other, &mut notes, vec![]);
+            assert!(notes_shown, "the closed section is still shown (invisibly)");
+        }
+        show(Tab::Notes, &mut notes, vec![]);
+        assert_eq!(notes, "draft");
+    }
+}
@@ -0,0 +1,210 @@
+use std::hash::Hash;
+
+use super::CollapsingState;
+use crate::*;
+
+/// A tree of [`CollapsingHeader`]s described up front, e.g. for a settings panel.
+///
+/// Groups become [`CollapsingHeader`]s, and leaves are shown with a closure of your choosing.
+/// Each group and leaf has an id source, which only needs to be unique among its siblings.
+///
+/// ```
+/// # egui::__run_test_ui(|ui| {
+/// use egui::collapsing_header::CollapsingSchema;
+///
+/// let schema = CollapsingSchema::new("settings")
+///     .group("audio", "Audio", |group| group.leaf("volume", "Volume"))
+///     .group("video", "Video", |group| {
+///         group
+///             .leaf("vsync", "VSync")
+///             .group("advanced", "Advanced", |group| group.leaf("msaa", "MSAA"))
+///     });
+/// if let Some(leaf) = schema.show(ui, |ui, leaf| ui.button(leaf.label.clone())) {
+///     // The leaf with this id was clicked or changed.
+/// }
+/// # });
+/// ```
+#[must_use = "You should call .show()"]
+pub struct CollapsingSchema {
+    id_source: Id,
+    nodes: Vec<SchemaNode>,
+    default_open: bool,
+    open_all: Option<bool>,
+}
+
+/// A leaf of a [`CollapsingSchema`].
+#[derive(Clone)]
+pub struct SchemaLeaf {
+    /// The id given to [`CollapsingSchema::leaf`].
+    pub id: Id,
+
+    pub label: WidgetText,
+}
+
+enum SchemaNode {
+    Group {
+        id: Id,
+        label: WidgetText,
+        children: Vec<SchemaNode>,
+    },
+    Leaf(SchemaLeaf),
+}
+
+impl CollapsingSchema {
+    pub fn new(id_source: impl Hash) -> Self {
+        Self {
+            id_source: Id::new(id_source),
+            nodes: Vec::new(),
+            default_open: false,
+            open_all: None,
+        }
+    }
+
+    /// Add a group, with the children added by `add_children`.
+    #[inline]
+    pub fn group(
+        mut self,
+        id_source: impl Hash,
+        label: impl Into<WidgetText>,
+        add_children: impl FnOnce(Self) -> Self,
+    ) -> Self {
+        let id = Id::new(id_source);
+        let children = add_children(Self::new(id)).nodes;
+        self.nodes.push(SchemaNode::Group {
+            id,
+            label: label.into(),
+            children,
+        });
+        self
+    }
+
+    /// Add a leaf, shown with the closure passed to [`Self::show`].
+    #[inline]
+    pub fn leaf(mut self, id_source: impl Hash, label: impl Into<WidgetText>) -> Self {
+        self.nodes.push(SchemaNode::Leaf(SchemaLeaf {
+            id: Id::new(id_source),
+            label: label.into(),
+        }));
+        self
+    }
+
+    /// Should the groups start open? Default: `false`.
+    #[inline]
+    pub fn default_open(mut self, default_open: bool) -> Self {
+        self.default_open = default_open;
+        self
+    }
+
+    /// `Some(true)` opens and `Some(false)` closes all groups this frame.
+    ///
+    /// See [`CollapsingHeader::open`].
+    #[inline]
+    pub fn open_all(mut self, open: Option<bool>) -> Self {
+        self.open_all = open;
+        self
+    }
+
+    /// Show the tree, using `leaf_ui` to show each visible leaf.
+    ///
+    /// Returns the [`SchemaLeaf::id`] of the leaf whose response was clicked or changed, if any.
+    pub fn show(
+        self,
+        ui: &mut Ui,
+        mut leaf_ui: impl FnMut(&mut Ui, &SchemaLeaf) -> Response,
+    ) -> Option<Id> {
+        let Self {
+            id_source,
+            nodes,
+            default_open,
+            open_all,
+        } = self;
+        ui.push_id(id_source, |ui| {
+            Self::show_nodes(ui, nodes, default_open, open_all, &mut leaf_ui)
+        })
+        .inner
+    }
+
+    fn show_nodes(
+        ui: &mut Ui,
+        nodes: Vec<SchemaNode>,
+        default_open: bool,
+        open_all: Option<bool>,
+        leaf_ui: &mut dyn FnMut(&mut Ui, &SchemaLeaf) -> Response,
+    ) -> Option<Id> {
+        let mut interacted = None;
+        for node in nodes {
+            match node {
+                SchemaNode::Group {
+                    id,
+                    label,
+                    children,
+                } => {
+                    let header = CollapsingHeader::new(label).id_source(id);
+                    let pinned = CollapsingState::load(ui.ctx(), header.resolve_id(ui))
+                        .map_or(false, |state| state.is_pinned());
+                    let response = header
+                        .default_open(default_open)
+                        .open(open_all.filter(|&open| open || !pinned))
+                        .show(ui, |ui| {
+                            Self::show_nodes(ui, children, default_open, open_all, leaf_ui)
+                        });
+                    interacted = interacted.or(response.body_returned.flatten());
+                }
+                SchemaNode::Leaf(leaf) => {
+                    let response = ui.push_id(leaf.id, |ui| leaf_ui(ui, &leaf)).inner;
+                    if response.clicked() || response.changed() {
+                        interacted = Some(leaf.id);
+                    }
+                }
+            }
+        }
+        interacted
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::run_ui;
+
+    #[test]
+    fn schema_ids_are_unique() {
+        fn schema() -> CollapsingSchema {
+            // The same id sources in different groups:
+            CollapsingSchema::new("settings")
+                .open_all(Some(true))
+                .group("a", "A", |group| {
+                    group
+                        .leaf("x", "X")
+                        .group("a", "Inner A", |group| group.leaf("x", "X"))
+                })
+                .group("b", "B", |group| group.leaf("x", "X"))
+        }
+
+        let ctx = Context::default();
+        let mut leaf_ids = vec![];
+        for _ in 0..2 {
+            leaf_ids.clear();
+            run_ui(&ctx, |ui| {
+                let interacted = schema().show(ui, |ui, leaf| {
+                    let response = ui.label(leaf.label.clone());
+                    leaf_ids.push(response.id);
+                    response
+                });
+                assert_eq!(interacted, None);
+            });
+        }
+        assert_eq!(leaf_ids.len(), 3);
+
+        let mut header_ids = CollapsingState::children_of(&ctx, Id::NULL);
+        let children = CollapsingState::children_of(&ctx, header_ids[0]);
+        header_ids.extend(children);
+        assert_eq!(header_ids.len(), 3);
+
+        let mut ids = [leaf_ids, header_ids].concat();
+        let count = ids.len();
+        ids.sort_by_key(|id| id.value());
+        ids.dedup();
+        assert_eq!(ids.len(), count);
+    }
+}
@@ -0,0 +1,94 @@
+use crate::*;
+
+/// Show the rows of the body that are inside the clip rectangle, see [`CollapsingHeader::show_rows`].
+pub(super) fn show_visible_rows<R>(
+    ui: &mut Ui,
+    row_height_sans_spacing: f32,
+    total_rows: usize,
+    add_rows: impl FnOnce(&mut Ui, std::ops::Range<usize>) -> R,
+) -> R {
+    let spacing = ui.spacing().item_spacing;
+    let row_height_with_spacing = row_height_sans_spacing + spacing.y;
+    let top = ui.max_rect().top();
+    ui.set_height((row_height_with_spacing * total_rows as f32 - spacing.y).at_least(0.0));
+
+    let visible = ui.clip_rect();
+    let min_row = ((visible.top() - top) / row_height_with_spacing)
+        .floor()
+        .max(0.0) as usize;
+    let max_row = ((visible.bottom() - top) / row_height_with_spacing)
+        .ceil()
+        .max(0.0) as usize;
+    let max_row = max_row.min(total_rows);
+    let min_row = min_row.min(max_row);
+
+    let y_min = top + min_row as f32 * row_height_with_spacing;
+    let y_max = top + max_row as f32 * row_height_with_spacing;
+    let rect = Rect::from_x_y_ranges(ui.max_rect().x_range(), y_min..=y_max);
+    ui.allocate_ui_at_rect(rect, |rows_ui| {
+        rows_ui.skip_ahead_auto_ids(min_row); // Make sure we get consistent IDs.
+        add_rows(rows_ui, min_row..max_row)
+    })
+    .inner
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{collapsing_header::CollapsingState, test_util::run_ui};
+
+    #[test]
+    fn show_rows_only_shows_visible_rows() {
+        let ctx = Context::default();
+        let row_height = 20.0;
+        let total_rows = 1000;
+        let show = || {
+            let mut result = None;
+            run_ui(&ctx, |ui| {
+                let mut shown = 0..0;
+                let response = CollapsingHeader::new("Rows").show_rows(
+                    ui,
+                    row_height,
+                    total_rows,
+                    |ui, rows| {
+                        for row in rows.clone() {
+                            ui.add_sized(vec2(100.0, row_height), Label::new(format!("Row {row}")));
+                        }
+                        shown = rows;
+                    },
+                );
+                let clip_height = ui.clip_rect().height();
+                result = Some((response.header_response.id, shown, clip_height));
+            });
+            result.unwrap()
+        };
+
+        let (id, _, clip_height) = show();
+        let mut state = CollapsingState::load(&ctx, id).unwrap();
+        state.set_open(true);
+        state.store(&ctx);
+
+        let max_visible_rows = (clip_height / row_height).ceil() as usize + 1;
+        let mut shown_rows = vec![];
+        for _ in 0..30 {
+            let (_, shown, _) = show();
+            assert!(shown.len() <= max_visible_rows, "{shown:?}");
+            shown_rows.push(shown.len());
+        }
+        assert!(
+            shown_rows[0] < *shown_rows.last().unwrap(),
+            "more rows are revealed while opening"
+        );
+
+        let spacing = ctx.style().spacing.item_spacing.y;
+        let full_height = CollapsingState::load(&ctx, id)
+            .unwrap()
+            .full_content_height()
+            .unwrap();
+        let expected = total_rows as f32 * (row_height + spacing) - spacing;
+        assert!(
+            (full_height - expected).abs() < 0.5,
+            "{full_height} {expected}"
+        );
+    }
+}
@@ -2,6 +2,7 @@
 //!
 //! For instance, a [`Frame`] adds a frame and background to some contained UI.
 
+mod accordion;
 pub(crate) mod area;
 pub mod collapsing_header;
 mod combo_box;
@@ -13,8 +14,12 @@ pub mod scroll_area;
 pub(crate) mod window;
 
 pub use {
+    accordion::{Accordion, AccordionUi},
     area::{Area, AreaState},
-    collapsing_header::{CollapsingHeader, CollapsingResponse},
+    collapsing_header::{
+        summary_details, AnimationStyle, ChipBody, CollapsingEvent, CollapsingHeader,
+        CollapsingIcon, CollapsingResponse, HeaderIconSide, RevealStyle,
+    },
     combo_box::*,
     frame::Frame,
     panel::{CentralPanel, SidePanel, TopBottomPanel},
@@ -447,15 +447,16 @@ impl<'open> Window<'open> {
             is_open,
             emath::easing::cubic_out,
         );
-        if opacity <= 0.0 {
+        let area_id = area.id;
+        let mut collapsing =
+            CollapsingState::load_with_default_open(ctx, area_id.with("collapsing"), default_open);
+        let visibility = collapsing.visibility(ctx);
+        if opacity <= 0.0 || visibility <= 0.0 {
             return None;
         }
 
-        let area_id = area.id;
         let area_layer_id = area.layer();
         let resize_id = area_id.with("resize");
-        let mut collapsing =
-            CollapsingState::load_with_default_open(ctx, area_id.with("collapsing"), default_open);
 
         let is_collapsed = with_title_bar && !collapsing.is_open();
         let possible = PossibleInteractions::new(&area, &resize, is_collapsed);
@@ -512,6 +513,7 @@ impl<'open> Window<'open> {
         } else if fade_out {
             area_content_ui.multiply_opacity(opacity);
         }
+        area_content_ui.multiply_opacity(visibility);
 
         let content_inner = {
             // BEGIN FRAME --------------------------------
@@ -478,7 +478,8 @@ pub struct WidgetInfo {
     /// The previous text value.
     pub prev_text_value: Option<String>,
 
-    /// The current value of checkboxes and radio buttons.
+    /// The current value of checkboxes and radio buttons,
+    /// or whether a collapsing header is open.
     pub selected: Option<bool>,
 
     /// The current value of sliders etc.
@@ -657,6 +658,9 @@ impl WidgetInfo {
             if *typ == WidgetType::Checkbox {
                 let state = if *selected { "checked" } else { "unchecked" };
                 description = format!("{state} {description}");
+            } else if *typ == WidgetType::CollapsingHeader {
+                let state = if *selected { "expanded" } else { "collapsed" };
+                description = format!("{state} {description}");
             } else {
                 description += if *selected { "selected" } else { "" };
             };
@@ -398,6 +398,8 @@ pub(crate) mod placer;
 mod response;
 mod sense;
 pub mod style;
+#[cfg(test)]
+mod test_util;
 pub mod text_selection;
 mod ui;
 mod ui_stack;
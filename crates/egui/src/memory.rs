@@ -215,6 +215,12 @@ pub struct Options {
     /// which `eframe` supports.
     pub screen_reader: bool,
 
+    /// If `true`, collapsing regions (e.g. [`crate::CollapsingHeader`]) open and close instantly,
+    /// instead of animating, for users who prefer reduced motion.
+    ///
+    /// Default is `false`.
+    pub reduce_collapse_animation: bool,
+
     /// If true, the most common glyphs (ASCII) are pre-rendered to the texture atlas.
     ///
     /// Only the fonts in [`Style::text_styles`] will be pre-cached.
@@ -267,6 +273,7 @@ impl Default for Options {
             tessellation_options: Default::default(),
             repaint_on_widget_change: false,
             screen_reader: false,
+            reduce_collapse_animation: false,
             preload_font_glyphs: true,
             warn_on_id_clash: cfg!(debug_assertions),
 
@@ -288,6 +295,7 @@ impl Options {
             tessellation_options,
             repaint_on_widget_change,
             screen_reader: _, // needs to come from the integration
+            reduce_collapse_animation,
             preload_font_glyphs: _,
             warn_on_id_clash,
 
@@ -313,6 +321,11 @@ impl Options {
 
                 ui.checkbox(warn_on_id_clash, "Warn if two widgets have the same Id");
 
+                ui.checkbox(
+                    reduce_collapse_animation,
+                    "Open and close collapsing regions instantly",
+                );
+
                 ui.checkbox(reduce_texture_memory, "Reduce texture memory");
             });
 
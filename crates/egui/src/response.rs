@@ -997,11 +997,15 @@ impl Response {
             builder.set_numeric_value(value);
         }
         if let Some(selected) = info.selected {
-            builder.set_checked(if selected {
-                Checked::True
+            if matches!(info.typ, WidgetType::CollapsingHeader) {
+                builder.set_expanded(selected);
             } else {
-                Checked::False
-            });
+                builder.set_checked(if selected {
+                    Checked::True
+                } else {
+                    Checked::False
+                });
+            }
         } else if matches!(info.typ, WidgetType::Checkbox) {
             // Indeterminate state
             builder.set_checked(Checked::Mixed);
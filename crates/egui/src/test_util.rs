@@ -0,0 +1,39 @@
+//! Helpers for running frames in unit tests.
+
+use crate::*;
+
+/// Run a frame 1/60 of a second after the previous one, without any input events.
+pub(crate) fn run_ui(ctx: &Context, add_contents: impl FnMut(&mut Ui)) {
+    run_frame(ctx, vec![], add_contents);
+}
+
+/// Run a frame 1/60 of a second after the previous one, with the given input events.
+pub(crate) fn run_frame(ctx: &Context, events: Vec<Event>, add_contents: impl FnMut(&mut Ui)) {
+    let input = RawInput {
+        events,
+        ..Default::default()
+    };
+    run_input(ctx, input, add_contents);
+}
+
+/// Press and release the primary mouse button at `pos`, where `show` runs a frame with the given events.
+///
+/// Returns what `show` returned for the frame the button was released in.
+pub(crate) fn click<R>(pos: Pos2, mut show: impl FnMut(Vec<Event>) -> R) -> R {
+    let button = |pressed| Event::PointerButton {
+        pos,
+        button: PointerButton::Primary,
+        pressed,
+        modifiers: Modifiers::NONE,
+    };
+    show(vec![Event::PointerMoved(pos), button(true)]);
+    show(vec![button(false)])
+}
+
+/// Run a frame 1/60 of a second after the previous one.
+pub(crate) fn run_input(ctx: &Context, mut input: RawInput, mut add_contents: impl FnMut(&mut Ui)) {
+    input.time = Some(ctx.input(|i| i.time) + 1.0 / 60.0);
+    let _ = ctx.run(input, |ctx| {
+        CentralPanel::default().show(ctx, |ui| add_contents(ui));
+    });
+}
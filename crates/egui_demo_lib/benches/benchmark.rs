@@ -84,6 +84,60 @@ pub fn criterion_benchmark(c: &mut Criterion) {
         // Don't call `end_frame` to not have to drain the huge paint list
     }
 
+    // Only the text layout is cached, the rest of each header is built as usual:
+    for cache_collapsed_galley in [false, true] {
+        let ctx = egui::Context::default();
+        let name = if cache_collapsed_galley {
+            "collapsed_headers_5000_cached_galley"
+        } else {
+            "collapsed_headers_5000"
+        };
+        c.bench_function(name, |b| {
+            b.iter(|| {
+                ctx.run(RawInput::default(), |ctx| {
+                    egui::CentralPanel::default().show(ctx, |ui| {
+                        for i in 0..5000 {
+                            egui::CollapsingHeader::new(format!("Header {i}"))
+                                .cache_collapsed_galley(cache_collapsed_galley)
+                                .show(ui, |ui| ui.label("Body"));
+                        }
+                    });
+                })
+            });
+        });
+    }
+
+    {
+        // A document of 100 paragraphs with about 10k glyphs in total:
+        let document: Vec<String> = (0..100)
+            .map(|i| LOREM_IPSUM_LONG.chars().skip(i).take(100).collect())
+            .collect();
+        for cache_body in [false, true] {
+            let ctx = egui::Context::default();
+            let name = if cache_body {
+                "collapsing_body_10k_glyphs_cached"
+            } else {
+                "collapsing_body_10k_glyphs"
+            };
+            c.bench_function(name, |b| {
+                b.iter(|| {
+                    ctx.run(RawInput::default(), |ctx| {
+                        egui::CentralPanel::default().show(ctx, |ui| {
+                            egui::CollapsingHeader::new("Document")
+                                .default_open(true)
+                                .cache_body(cache_body)
+                                .show(ui, |ui| {
+                                    for paragraph in &document {
+                                        ui.label(paragraph.as_str());
+                                    }
+                                });
+                        });
+                    })
+                });
+            });
+        }
+    }
+
     {
         let pixels_per_point = 1.0;
         let max_texture_side = 8 * 1024;